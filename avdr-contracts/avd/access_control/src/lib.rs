@@ -1,7 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use l1x_sdk::store::LookupMap;
-use l1x_sdk::types::Address;
-use l1x_sdk::{caller_address, contract, contract_owner_address};
+use l1x_sdk::store::{LookupMap, Vector};
+use l1x_sdk::types::{Address, TimeStamp, U128};
+use l1x_sdk::{block_timestamp, caller_address, contract, contract_owner_address};
 use serde::Serialize;
 use types::access_control_interface::{Permission, ReqIdx};
 use types::data_storage_interface::DataStorageContract;
@@ -10,11 +10,30 @@ use types::{Cid, PublicKey};
 use std::collections::{BTreeMap, BTreeSet};
 
 const STORAGE_CONTRACT_KEY: &[u8] = b"STATE";
-const STORAGE_PERMISSIONS_KEY: &[u8] = b"PERMISSIONS";
+const STORAGE_STATE_HEADER_KEY: &[u8] = b"STATE_HEADER";
+/// Permissions of `LEGACY_STATE_VERSION`, moved to `STORAGE_GRANTS_KEY` by `migrate_permissions`
+const STORAGE_LEGACY_PERMISSIONS_KEY: &[u8] = b"PERMISSIONS";
+/// Statuses of the requests resolved before `migrate`
+const STORAGE_LEGACY_REQ_STATUS_KEY: &[u8] = b"REQ_STATUS";
+const STORAGE_GRANTS_KEY: &[u8] = b"GRANTS";
 const STORAGE_SHARED_KEY: &[u8] = b"SHARED";
-const STORAGE_REQ_STATUS_KEY: &[u8] = b"REQ_STATUS";
+const STORAGE_REQUESTS_KEY: &[u8] = b"REQUESTS";
+const STORAGE_PENDING_BY_CID_KEY: &[u8] = b"PENDING_BY_CID";
+const STORAGE_PENDING_BY_USER_KEY: &[u8] = b"PENDING_BY_USER";
+/// Key suffix for the storage of the pending requests of a cid
+const STORAGE_PENDING_BY_CID_VEC_KEY: &[u8] = b"PENDING_CID";
+/// Key suffix for the storage of the pending requests of a user
+const STORAGE_PENDING_BY_USER_VEC_KEY: &[u8] = b"PENDING_USER";
 const STORAGE_DENIED_PUB_KEY: &[u8] = b"DENIED_PUB_KEYS";
 const STORAGE_DENIED_CIDS_KEY: &[u8] = b"DENIED_CIDS";
+const STORAGE_ROLES_KEY: &[u8] = b"ROLES";
+const STORAGE_ROLE_MEMBERS_KEY: &[u8] = b"ROLE_MEMBERS";
+
+/// Layout of the contract data before the state header was introduced
+const LEGACY_STATE_VERSION: u32 = 1;
+
+/// Current layout of the contract data
+const STATE_VERSION: u32 = 2;
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug)]
 pub struct ShareRequest {
@@ -28,6 +47,8 @@ pub struct PermissionRequest {
     pub user: Address,
     pub cid: Cid,
     pub perm: Permission,
+    /// The grant expires at this block timestamp, `None` requests a permanent grant
+    pub expires_at: Option<U128>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug)]
@@ -56,18 +77,98 @@ pub struct PendingRequest {
     pub req: Request,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub enum RequestStatus {
     Pending,
     Rejected,
     Approved,
 }
 
+/// Positions of a pending request in the `pending_by_cid` and `pending_by_user` vectors
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug)]
+struct PendingPosition {
+    by_cid: u32,
+    by_user: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+struct RequestRecord {
+    req_idx: ReqIdx,
+    req: Request,
+    status: RequestStatus,
+    /// The address that approved or rejected the request
+    approver: Option<Address>,
+    /// `None` once the request is resolved
+    pending: Option<PendingPosition>,
+}
+
+/// A request with its status as returned by `request`
+#[derive(Serialize, Clone, Debug)]
+pub struct RequestInfo {
+    pub req_idx: ReqIdx,
+    /// `None` for the requests resolved before `migrate`, only their status is kept
+    pub req: Option<Request>,
+    pub status: RequestStatus,
+    /// The address that approved or rejected the request
+    pub approver: Option<Address>,
+}
+
+/// A named set of permissions of a cid
+#[derive(Serialize, Clone, Debug)]
+pub struct Role {
+    pub name: String,
+    pub perms: Vec<Permission>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+struct Grant {
+    perm: Permission,
+    /// The block timestamp when the grant expires, `None` never expires
+    expires_at: Option<TimeStamp>,
+}
+
+/// At most one grant per permission. Not a map keyed by the permission because borsh requires the keys to be `Hash`
+type Grants = Vec<Grant>;
+
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct Contract {
-    pending_requests: BTreeMap<ReqIdx, Request>,
+struct StateHeader {
+    version: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct PermissionRequestV1 {
+    user: Address,
+    cid: Cid,
+    perm: Permission,
+}
+
+#[derive(BorshDeserialize)]
+enum RequestV1 {
+    Permission(PermissionRequestV1),
+    Share(ShareRequest),
+}
+
+impl From<RequestV1> for Request {
+    fn from(req: RequestV1) -> Self {
+        match req {
+            RequestV1::Permission(PermissionRequestV1 { user, cid, perm }) => {
+                Request::Permission(PermissionRequest { user, cid, perm, expires_at: None })
+            }
+            RequestV1::Share(req) => Request::Share(req),
+        }
+    }
+}
+
+/// Contract data of `LEGACY_STATE_VERSION`. Used only by `migrate`
+#[derive(BorshDeserialize)]
+struct ContractV1 {
+    pending_requests: BTreeMap<ReqIdx, RequestV1>,
+    /// Left in storage for `migrate_permissions`
+    #[allow(dead_code)]
     approved_permissions: LookupMap<Cid, BTreeMap<Address, BTreeSet<Permission>>>,
     approved_shares: LookupMap<Cid, BTreeSet<Cid>>,
+    /// Left in storage for `status_of_request`
+    #[allow(dead_code)]
     request_status: LookupMap<ReqIdx, RequestStatus>,
 
     denied_pub_keys: LookupMap<PublicKey, ()>,
@@ -76,6 +177,25 @@ pub struct Contract {
     requests_counter: u64,
 }
 
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Contract {
+    requests: LookupMap<ReqIdx, RequestRecord>,
+    pending_by_cid: LookupMap<Cid, Vector<u64>>,
+    pending_by_user: LookupMap<Address, Vector<u64>>,
+    approved_permissions: LookupMap<Cid, BTreeMap<Address, Grants>>,
+    approved_shares: LookupMap<Cid, BTreeSet<Cid>>,
+
+    denied_pub_keys: LookupMap<PublicKey, ()>,
+    denied_cids: LookupMap<Cid, ()>,
+
+    /// Role name -> the permissions granted by the role
+    roles: LookupMap<Cid, BTreeMap<String, BTreeSet<Permission>>>,
+    /// User -> the names of the roles assigned to the user
+    role_members: LookupMap<Cid, BTreeMap<Address, BTreeSet<String>>>,
+
+    requests_counter: u64,
+}
+
 #[contract]
 impl Contract {
     pub fn new() {
@@ -83,15 +203,68 @@ impl Contract {
         Self::assert_if_initialized();
 
         Self {
-            pending_requests: BTreeMap::new(),
-            approved_permissions: LookupMap::new(STORAGE_PERMISSIONS_KEY.to_vec()),
+            requests: LookupMap::new(STORAGE_REQUESTS_KEY.to_vec()),
+            pending_by_cid: LookupMap::new(STORAGE_PENDING_BY_CID_KEY.to_vec()),
+            pending_by_user: LookupMap::new(STORAGE_PENDING_BY_USER_KEY.to_vec()),
+            approved_permissions: LookupMap::new(STORAGE_GRANTS_KEY.to_vec()),
             approved_shares: LookupMap::new(STORAGE_SHARED_KEY.to_vec()),
-            request_status: LookupMap::new(STORAGE_REQ_STATUS_KEY.to_vec()),
             denied_pub_keys: LookupMap::new(STORAGE_DENIED_PUB_KEY.to_vec()),
             denied_cids: LookupMap::new(STORAGE_DENIED_CIDS_KEY.to_vec()),
+            roles: LookupMap::new(STORAGE_ROLES_KEY.to_vec()),
+            role_members: LookupMap::new(STORAGE_ROLE_MEMBERS_KEY.to_vec()),
             requests_counter: 0,
         }
         .save();
+        Self::save_state_version_internal(STATE_VERSION);
+    }
+
+    /// Converts the contract data of an older layout in place. The pending requests are indexed again.
+    ///
+    /// The approved permissions can't be iterated, they are moved by `migrate_permissions`
+    pub fn migrate() {
+        assert_eq!(caller_address(), contract_owner_address(), "Only the owner can call this function");
+        assert_eq!(Self::state_version_internal(), LEGACY_STATE_VERSION, "The contract state is already migrated");
+
+        let legacy = match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => ContractV1::try_from_slice(&bytes).unwrap(),
+            None => panic!("The contract isn't initialized"),
+        };
+
+        let mut contract = Self {
+            requests: LookupMap::new(STORAGE_REQUESTS_KEY.to_vec()),
+            pending_by_cid: LookupMap::new(STORAGE_PENDING_BY_CID_KEY.to_vec()),
+            pending_by_user: LookupMap::new(STORAGE_PENDING_BY_USER_KEY.to_vec()),
+            approved_permissions: LookupMap::new(STORAGE_GRANTS_KEY.to_vec()),
+            approved_shares: legacy.approved_shares,
+            denied_pub_keys: legacy.denied_pub_keys,
+            denied_cids: legacy.denied_cids,
+            roles: LookupMap::new(STORAGE_ROLES_KEY.to_vec()),
+            role_members: LookupMap::new(STORAGE_ROLE_MEMBERS_KEY.to_vec()),
+            requests_counter: legacy.requests_counter,
+        };
+        for (req_idx, req) in legacy.pending_requests {
+            contract.add_pending_request_internal(req_idx, req.into());
+        }
+
+        contract.save();
+        Self::save_state_version_internal(STATE_VERSION);
+
+        l1x_sdk::msg(&format!(
+            "Migrated the contract state from version {} to {}",
+            LEGACY_STATE_VERSION, STATE_VERSION
+        ));
+    }
+
+    /// Moves the permissions of `cids` approved before `migrate` as permanent grants. Cids without such
+    /// permissions are skipped
+    pub fn migrate_permissions(cids: Vec<Cid>) {
+        let mut contract = Self::load();
+        contract.migrate_permissions_internal(cids);
+        contract.save();
+    }
+
+    pub fn state_version() -> u32 {
+        Self::state_version_internal()
     }
 
     pub fn permissions(user: Address, cid: Cid) -> Vec<Permission> {
@@ -119,18 +292,24 @@ impl Contract {
         contract.status_of_request_internal(req_idx)
     }
 
-    pub fn request_perm(cid: Cid, perm: Permission) -> ReqIdx {
+    pub fn request(req_idx: ReqIdx) -> RequestInfo {
+        let contract = Self::load();
+        contract.request_info_internal(req_idx)
+    }
+
+    pub fn request_perm(cid: Cid, perm: Permission, expires_at: Option<U128>) -> ReqIdx {
         let mut contract = Self::load();
-        let req = Request::Permission(PermissionRequest { user: caller_address(), cid, perm });
+        let req = Request::Permission(PermissionRequest { user: caller_address(), cid, perm, expires_at });
         let req_idx = contract.request_perm_internal(req);
         contract.save();
 
         req_idx
     }
 
-    pub fn approve_perm(req_idx: ReqIdx) {
+    /// Approves the permission request. `expires_at` overrides the expiry of the request
+    pub fn approve_perm(req_idx: ReqIdx, expires_at: Option<U128>) {
         let mut contract = Self::load();
-        contract.approve_perm_internal(req_idx, None);
+        contract.approve_perm_internal(req_idx, None, expires_at);
         contract.save();
     }
 
@@ -145,7 +324,7 @@ impl Contract {
 
     pub fn approve_share(req_idx: ReqIdx, shared_cid: Cid) {
         let mut contract = Self::load();
-        contract.approve_perm_internal(req_idx, Some(shared_cid));
+        contract.approve_perm_internal(req_idx, Some(shared_cid), None);
         contract.save();
     }
 
@@ -161,26 +340,97 @@ impl Contract {
         contract.save();
     }
 
-    pub fn pending_requests_by_cid(cid: Cid) -> Vec<PendingRequest> {
+    /// Removes the expired grants of `cid`
+    pub fn prune_expired(cid: Cid) {
+        let mut contract = Self::load();
+        contract.prune_expired_internal(cid);
+        contract.save();
+    }
+
+    /// Creates the role or replaces its permissions. Requires the 'approve' permission
+    pub fn set_role(cid: Cid, role: String, perms: Vec<Permission>) {
+        let mut contract = Self::load();
+        contract.set_role_internal(cid, role, perms);
+        contract.save();
+    }
+
+    /// Removes the role and unassigns it from its members. Requires the 'revoke' permission
+    pub fn remove_role(cid: Cid, role: String) {
+        let mut contract = Self::load();
+        contract.remove_role_internal(cid, role);
+        contract.save();
+    }
+
+    /// Assigns the role to all `users`. Requires the 'approve' permission
+    pub fn assign_role(cid: Cid, role: String, users: Vec<Address>) {
+        let mut contract = Self::load();
+        contract.assign_role_internal(cid, role, users);
+        contract.save();
+    }
+
+    /// Unassigns the role from all `users`. Requires the 'revoke' permission
+    pub fn unassign_role(cid: Cid, role: String, users: Vec<Address>) {
+        let mut contract = Self::load();
+        contract.unassign_role_internal(cid, role, users);
+        contract.save();
+    }
+
+    pub fn roles(cid: Cid) -> Vec<Role> {
         let contract = Self::load();
 
         contract
-            .pending_requests
-            .into_iter()
-            .filter(|(_, req)| req.cid().clone() == cid)
-            .map(|(req_idx, req)| PendingRequest { req_idx, req })
-            .collect::<_>()
+            .roles
+            .get(&cid)
+            .map(|roles| {
+                roles
+                    .iter()
+                    .map(|(name, perms)| Role { name: name.clone(), perms: perms.iter().cloned().collect() })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn role_members(cid: Cid, role: String) -> Vec<Address> {
+        let contract = Self::load();
+
+        contract
+            .role_members
+            .get(&cid)
+            .map(|members| members.iter().filter(|(_, roles)| roles.contains(&role)).map(|(user, _)| *user).collect())
+            .unwrap_or_default()
     }
 
-    pub fn pending_requests_by_user(user: Address) -> Vec<PendingRequest> {
+    pub fn roles_of(user: Address, cid: Cid) -> Vec<String> {
         let contract = Self::load();
 
         contract
-            .pending_requests
-            .into_iter()
-            .filter(|(_, req)| req.user().clone() == user)
-            .map(|(req_idx, req)| PendingRequest { req_idx, req })
-            .collect::<_>()
+            .role_members
+            .get(&cid)
+            .and_then(|members| members.get(&user))
+            .map(|roles| roles.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the pending requests of `cid` skipping the first `from` ones, at most `limit` of them if it's set
+    pub fn pending_requests_by_cid(cid: Cid, from: Option<u32>, limit: Option<u32>) -> Vec<PendingRequest> {
+        let contract = Self::load();
+        contract.pending_requests_internal(contract.pending_by_cid.get(&cid), from, limit)
+    }
+
+    /// Returns the pending requests of `user` skipping the first `from` ones, at most `limit` of them if it's set
+    pub fn pending_requests_by_user(user: Address, from: Option<u32>, limit: Option<u32>) -> Vec<PendingRequest> {
+        let contract = Self::load();
+        contract.pending_requests_internal(contract.pending_by_user.get(&user), from, limit)
+    }
+
+    pub fn pending_requests_count_by_cid(cid: Cid) -> u32 {
+        let contract = Self::load();
+        contract.pending_by_cid.get(&cid).map_or(0, |req_idxs| req_idxs.len())
+    }
+
+    pub fn pending_requests_count_by_user(user: Address) -> u32 {
+        let contract = Self::load();
+        contract.pending_by_user.get(&user).map_or(0, |req_idxs| req_idxs.len())
     }
 
     pub fn deny_pubkey(pub_key: PublicKey) {
@@ -213,7 +463,23 @@ impl Contract {
         assert!(l1x_sdk::storage_read(STORAGE_CONTRACT_KEY).is_none(), "The contract is already initialized");
     }
 
+    fn state_version_internal() -> u32 {
+        match l1x_sdk::storage_read(STORAGE_STATE_HEADER_KEY) {
+            Some(bytes) => StateHeader::try_from_slice(&bytes).unwrap().version,
+            None => LEGACY_STATE_VERSION,
+        }
+    }
+
+    fn save_state_version_internal(version: u32) {
+        l1x_sdk::storage_write(STORAGE_STATE_HEADER_KEY, &StateHeader { version }.try_to_vec().unwrap());
+    }
+
     fn load() -> Self {
+        assert_eq!(
+            Self::state_version_internal(),
+            STATE_VERSION,
+            "The contract state is outdated, call `migrate` first"
+        );
         match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
             Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
             None => panic!("The contract isn't initialized"),
@@ -239,10 +505,42 @@ impl Contract {
         if user == owner {
             vec![Permission::Write, Permission::Remove, Permission::Approve, Permission::Reject, Permission::Revoke]
         } else {
-            let users = self.approved_permissions.get(&cid).expect("The provided cid is not found");
-            let perms = users.get(&user).expect("The provided user is not found");
+            let users = self.approved_permissions.get(&cid);
+            let members = self.role_members.get(&cid);
+            assert!(users.is_some() || members.is_some(), "The provided cid is not found");
+
+            let grants = users.and_then(|users| users.get(&user));
+            let roles = members.and_then(|members| members.get(&user));
+            assert!(grants.is_some() || roles.is_some(), "The provided user is not found");
+
+            let now = block_timestamp();
+            let mut perms = grants
+                .into_iter()
+                .flatten()
+                .filter(|grant| Self::is_active(&grant.expires_at, now))
+                .map(|grant| grant.perm.clone())
+                .collect::<BTreeSet<_>>();
+
+            if let (Some(roles), Some(role_perms)) = (roles, self.roles.get(&cid)) {
+                for role in roles {
+                    perms.extend(role_perms.get(role).into_iter().flatten().cloned());
+                }
+            }
+
+            perms.into_iter().collect::<_>()
+        }
+    }
+
+    fn is_active(expires_at: &Option<TimeStamp>, now: TimeStamp) -> bool {
+        match expires_at {
+            Some(expires_at) => *expires_at > now,
+            None => true,
+        }
+    }
 
-            perms.iter().cloned().collect::<_>()
+    fn assert_expiry(expires_at: &Option<U128>) {
+        if let Some(expires_at) = expires_at {
+            assert!(expires_at.0 > block_timestamp(), "The expiry should be in the future");
         }
     }
 
@@ -261,23 +559,159 @@ impl Contract {
     }
 
     fn status_of_request_internal(&self, req_idx: ReqIdx) -> RequestStatus {
-        self.request_status.get(&req_idx).expect("The provided request is not found").clone()
+        if let Some(record) = self.requests.get(&req_idx) {
+            return record.status.clone();
+        }
+
+        // Only the status is kept for the requests resolved before `migrate`
+        let legacy_status = LookupMap::<ReqIdx, RequestStatus>::new(STORAGE_LEGACY_REQ_STATUS_KEY.to_vec());
+        legacy_status.get(&req_idx).cloned().expect("The provided request is not found")
+    }
+
+    fn request_info_internal(&self, req_idx: ReqIdx) -> RequestInfo {
+        match self.requests.get(&req_idx) {
+            Some(record) => RequestInfo {
+                req_idx,
+                req: Some(record.req.clone()),
+                status: record.status.clone(),
+                approver: record.approver,
+            },
+            None => RequestInfo {
+                req_idx: req_idx.clone(),
+                req: None,
+                status: self.status_of_request_internal(req_idx),
+                approver: None,
+            },
+        }
+    }
+
+    fn request_internal(&self, req_idx: &ReqIdx) -> &RequestRecord {
+        self.requests.get(req_idx).expect("The provided request is not found")
+    }
+
+    fn pending_request_internal(&self, req_idx: &ReqIdx) -> &Request {
+        let record = self.request_internal(req_idx);
+        assert_eq!(record.status, RequestStatus::Pending, "The provided request is not pending");
+        &record.req
+    }
+
+    fn pending_requests_internal(
+        &self,
+        req_idxs: Option<&Vector<u64>>,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<PendingRequest> {
+        let req_idxs = match req_idxs {
+            Some(req_idxs) => req_idxs,
+            None => return Vec::new(),
+        };
+        let from = from.unwrap_or(0).min(req_idxs.len());
+        let to = limit.map_or(req_idxs.len(), |limit| from.saturating_add(limit).min(req_idxs.len()));
+
+        (from..to)
+            .map(|position| {
+                let req_idx = ReqIdx((*req_idxs.get(position).expect("The position is checked above")).into());
+                let req = self.request_internal(&req_idx).req.clone();
+                PendingRequest { req_idx, req }
+            })
+            .collect::<_>()
+    }
+
+    /// Marks the pending request as resolved and removes it from the pending indexes
+    fn resolve_request_internal(&mut self, req_idx: ReqIdx, status: RequestStatus, approver: Option<Address>) {
+        let record = self.requests.get_mut(&req_idx).expect("The provided request is not found");
+        record.status = status;
+        record.approver = approver;
+        let position = record.pending.take().expect("The request is not pending");
+
+        let cid = record.req.cid().clone();
+        let user = *record.req.user();
+
+        // The last request of the index is moved to the position of the removed one
+        if let Some(moved) = Self::swap_remove_pending_internal(&mut self.pending_by_cid, &cid, position.by_cid) {
+            self.pending_position_internal(moved).by_cid = position.by_cid;
+        }
+        if let Some(moved) = Self::swap_remove_pending_internal(&mut self.pending_by_user, &user, position.by_user) {
+            self.pending_position_internal(moved).by_user = position.by_user;
+        }
+    }
+
+    fn pending_position_internal(&mut self, req_idx: u64) -> &mut PendingPosition {
+        self.requests
+            .get_mut(&ReqIdx(req_idx.into()))
+            .and_then(|record| record.pending.as_mut())
+            .expect("Can't find the position of the moved pending request")
+    }
+
+    /// Appends the request to the pending requests of `key` and returns its position
+    fn push_pending_internal<K: BorshSerialize + Ord + Clone>(
+        index: &mut LookupMap<K, Vector<u64>>,
+        key: &K,
+        prefix: Vec<u8>,
+        req_idx: u64,
+    ) -> u32 {
+        if !index.contains_key(key) {
+            index.insert(key.clone(), Vector::new(prefix));
+        }
+        let req_idxs = index.get_mut(key).expect("The pending requests are inserted above");
+        req_idxs.push(req_idx);
+
+        req_idxs.len() - 1
+    }
+
+    /// Removes the request at `position` from the pending requests of `key`. Returns the request moved to
+    /// `position`, `None` if the removed request was the last one
+    fn swap_remove_pending_internal<K: BorshSerialize + Ord + Clone>(
+        index: &mut LookupMap<K, Vector<u64>>,
+        key: &K,
+        position: u32,
+    ) -> Option<u64> {
+        let req_idxs = index.get_mut(key).expect("Can't find the pending requests");
+        req_idxs.swap_remove(position);
+
+        req_idxs.get(position).copied()
     }
 
     fn request_perm_internal(&mut self, req: Request) -> ReqIdx {
         self.assert_if_denied(&req);
+        if let Request::Permission(req) = &req {
+            Self::assert_expiry(&req.expires_at);
+        }
 
         self.requests_counter += 1;
         let req_idx = ReqIdx(self.requests_counter.into());
-
-        self.pending_requests.insert(req_idx.clone(), req);
-        self.request_status.insert(req_idx.clone(), RequestStatus::Pending);
+        self.add_pending_request_internal(req_idx.clone(), req);
 
         req_idx
     }
 
-    fn approve_perm_internal(&mut self, req_idx: ReqIdx, shared_cid: Option<Cid>) {
-        let req = self.pending_requests.get(&req_idx).expect("The provided request is not found");
+    fn add_pending_request_internal(&mut self, req_idx: ReqIdx, req: Request) {
+        let cid = req.cid().clone();
+        let user = *req.user();
+
+        let position = PendingPosition {
+            by_cid: Self::push_pending_internal(
+                &mut self.pending_by_cid,
+                &cid,
+                [cid.0.to_vec().as_slice(), STORAGE_PENDING_BY_CID_VEC_KEY].concat(),
+                req_idx.0 .0,
+            ),
+            by_user: Self::push_pending_internal(
+                &mut self.pending_by_user,
+                &user,
+                [user.to_vec().as_slice(), STORAGE_PENDING_BY_USER_VEC_KEY].concat(),
+                req_idx.0 .0,
+            ),
+        };
+
+        self.requests.insert(
+            req_idx.clone(),
+            RequestRecord { req_idx, req, status: RequestStatus::Pending, approver: None, pending: Some(position) },
+        );
+    }
+
+    fn approve_perm_internal(&mut self, req_idx: ReqIdx, shared_cid: Option<Cid>, expires_at: Option<U128>) {
+        let req = self.pending_request_internal(&req_idx).clone();
 
         self.assert_if_denied(&req);
         assert!(
@@ -285,28 +719,18 @@ impl Contract {
             "The caller doesn't have 'approve' permission"
         );
 
-        match req {
+        match &req {
             Request::Permission(req) => {
                 assert!(shared_cid.is_none(), "WARNING: Shared CID is ignored in this request.");
 
-                if let Some(users) = self.approved_permissions.get_mut(&req.cid) {
-                    if let Some(perms) = users.get_mut(&req.user) {
-                        perms.insert(req.perm.clone());
-                    } else {
-                        let mut perms = BTreeSet::new();
-                        perms.insert(req.perm.clone());
-                        users.insert(req.user, perms);
-                    }
-                } else {
-                    let mut perms = BTreeSet::new();
-                    perms.insert(req.perm.clone());
-                    let mut users = BTreeMap::new();
+                let expires_at = expires_at.or(req.expires_at);
+                Self::assert_expiry(&expires_at);
+                let expires_at = expires_at.map(|expires_at| expires_at.0);
 
-                    users.insert(req.user, perms);
-                    self.approved_permissions.insert(req.cid.clone(), users);
-                }
+                self.grant_internal(req.cid.clone(), req.user, Grant { perm: req.perm.clone(), expires_at });
             }
             Request::Share(req) => {
+                assert!(expires_at.is_none(), "Share requests don't expire");
                 let shared_cid = shared_cid.expect("Shared CID is required");
                 assert!(!self.approved_shares.contains_key(&shared_cid), "The provided Shared CID is already approved");
 
@@ -323,21 +747,19 @@ impl Contract {
             }
         }
 
-        self.pending_requests.remove(&req_idx);
-        self.request_status.insert(req_idx, RequestStatus::Approved);
+        self.resolve_request_internal(req_idx, RequestStatus::Approved, Some(caller_address()));
     }
 
     fn reject_perm_internal(&mut self, req_idx: ReqIdx) {
         // Don't check whether pub_key is denied because then we will not be to able to remove the pending request
         // self.assert_if_denied(&pub_key);
-        let req = self.pending_requests.get(&req_idx).expect("The provided req_idx is not found");
+        let req = self.pending_request_internal(&req_idx);
         assert!(
             self.has_perm_internal(caller_address(), req.cid().clone(), Permission::Reject),
             "The caller doesn't have 'reject' permission"
         );
 
-        let _old_req = self.pending_requests.remove(&req_idx).expect("The provided request is not found");
-        self.request_status.insert(req_idx, RequestStatus::Rejected);
+        self.resolve_request_internal(req_idx, RequestStatus::Rejected, Some(caller_address()));
     }
 
     fn revoke_perm_internal(&mut self, cid: Cid, user: Address, perm: Permission) {
@@ -352,7 +774,9 @@ impl Contract {
         let users = self.approved_permissions.get_mut(&cid).expect("The provided cid is not found");
         let perms = users.get_mut(&user).expect("The provided user is not found");
 
-        assert!(perms.remove(&perm), "The user didn't have such permissions for the provided cid");
+        let perms_count = perms.len();
+        perms.retain(|grant| grant.perm != perm);
+        assert!(perms.len() < perms_count, "The user didn't have such permissions for the provided cid");
 
         if perms.is_empty() {
             users.remove(&user);
@@ -363,6 +787,135 @@ impl Contract {
         }
     }
 
+    fn prune_expired_internal(&mut self, cid: Cid) {
+        let users = self.approved_permissions.get_mut(&cid).expect("The provided cid is not found");
+        let now = block_timestamp();
+
+        for perms in users.values_mut() {
+            perms.retain(|grant| Self::is_active(&grant.expires_at, now));
+        }
+        users.retain(|_, perms| !perms.is_empty());
+
+        if users.is_empty() {
+            self.approved_permissions.remove(cid);
+        }
+    }
+
+    /// Replaces the grant of the same permission
+    fn grant_internal(&mut self, cid: Cid, user: Address, grant: Grant) {
+        if !self.approved_permissions.contains_key(&cid) {
+            self.approved_permissions.insert(cid.clone(), BTreeMap::new());
+        }
+        let users = self.approved_permissions.get_mut(&cid).expect("The users of the cid are inserted above");
+        let perms = users.entry(user).or_default();
+
+        perms.retain(|existing| existing.perm != grant.perm);
+        perms.push(grant);
+    }
+
+    fn migrate_permissions_internal(&mut self, cids: Vec<Cid>) {
+        assert_eq!(contract_owner_address(), caller_address(), "Only the contract owner can call this function");
+
+        let mut legacy_permissions: LookupMap<Cid, BTreeMap<Address, BTreeSet<Permission>>> =
+            LookupMap::new(STORAGE_LEGACY_PERMISSIONS_KEY.to_vec());
+
+        for cid in cids {
+            let users = legacy_permissions.remove(cid.clone()).unwrap_or_default();
+            for (user, perms) in users {
+                for perm in perms {
+                    // A grant approved after `migrate` is newer than the legacy one
+                    let granted = self
+                        .approved_permissions
+                        .get(&cid)
+                        .and_then(|users| users.get(&user))
+                        .is_some_and(|grants| grants.iter().any(|grant| grant.perm == perm));
+                    if !granted {
+                        self.grant_internal(cid.clone(), user, Grant { perm, expires_at: None });
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_role_internal(&mut self, cid: Cid, role: String, perms: Vec<Permission>) {
+        assert!(
+            self.has_perm_internal(caller_address(), cid.clone(), Permission::Approve),
+            "The caller doesn't have 'approve' permission"
+        );
+        assert!(!role.is_empty(), "The role name should not be empty");
+        assert!(!perms.is_empty(), "The role should grant at least one permission");
+
+        let perms = perms.into_iter().collect::<BTreeSet<_>>();
+        if let Some(roles) = self.roles.get_mut(&cid) {
+            roles.insert(role, perms);
+        } else {
+            let mut roles = BTreeMap::new();
+            roles.insert(role, perms);
+            self.roles.insert(cid, roles);
+        }
+    }
+
+    fn remove_role_internal(&mut self, cid: Cid, role: String) {
+        assert!(
+            self.has_perm_internal(caller_address(), cid.clone(), Permission::Revoke),
+            "The caller doesn't have 'revoke' permission"
+        );
+
+        let roles = self.roles.get_mut(&cid).expect("The provided cid is not found");
+        assert!(roles.remove(&role).is_some(), "The provided role is not found");
+        if roles.is_empty() {
+            self.roles.remove(cid.clone());
+        }
+
+        if let Some(members) = self.role_members.get_mut(&cid) {
+            for roles in members.values_mut() {
+                roles.remove(&role);
+            }
+            members.retain(|_, roles| !roles.is_empty());
+
+            if members.is_empty() {
+                self.role_members.remove(cid);
+            }
+        }
+    }
+
+    fn assign_role_internal(&mut self, cid: Cid, role: String, users: Vec<Address>) {
+        assert!(
+            self.has_perm_internal(caller_address(), cid.clone(), Permission::Approve),
+            "The caller doesn't have 'approve' permission"
+        );
+        assert!(self.roles.get(&cid).is_some_and(|roles| roles.contains_key(&role)), "The provided role is not found");
+
+        if !self.role_members.contains_key(&cid) {
+            self.role_members.insert(cid.clone(), BTreeMap::new());
+        }
+        let members = self.role_members.get_mut(&cid).expect("The members of the cid are inserted above");
+        for user in users {
+            members.entry(user).or_default().insert(role.clone());
+        }
+    }
+
+    fn unassign_role_internal(&mut self, cid: Cid, role: String, users: Vec<Address>) {
+        assert!(
+            self.has_perm_internal(caller_address(), cid.clone(), Permission::Revoke),
+            "The caller doesn't have 'revoke' permission"
+        );
+
+        let members = self.role_members.get_mut(&cid).expect("The provided cid is not found");
+        for user in users {
+            let roles = members.get_mut(&user).expect("The provided user is not found");
+            assert!(roles.remove(&role), "The user didn't have such role for the provided cid");
+
+            if roles.is_empty() {
+                members.remove(&user);
+            }
+        }
+
+        if members.is_empty() {
+            self.role_members.remove(cid);
+        }
+    }
+
     fn deny_pubkey_internal(&mut self, pub_key: PublicKey) {
         assert_eq!(contract_owner_address(), caller_address(), "Only the contract owner can call this function");
 
@@ -1,11 +1,7 @@
 use crate::Cid;
 use borsh::{BorshDeserialize, BorshSerialize};
-use l1x_sdk::{
-    call_contract,
-    contract_interaction::ContractCall,
-    gas_left,
-    types::{Address, Gas, U64},
-};
+use l1x_contract_client::contract_client;
+use l1x_sdk::types::{Address, U64};
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
@@ -29,78 +25,9 @@ pub enum Permission {
     Revoke,
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Debug)]
-pub struct AccessControlContract {
-    address: Address,
-}
-
-impl AccessControlContract {
-    pub fn new(address: Address) -> Self {
-        Self { address }
-    }
-
-    pub fn permissions(&self, user: Address, cid: Cid) -> Vec<Permission> {
-        let args = {
-            #[derive(Serialize)]
-            struct Args {
-                user: Address,
-                cid: Cid,
-            }
-            serde_json::to_vec(&Args { user, cid }).unwrap()
-        };
-        self.call("permissions", args, gas_left().saturating_sub(10_000))
-    }
-
-    pub fn has_perm(&self, user: Address, cid: Cid, perm: Permission) -> bool {
-        let args = {
-            #[derive(Serialize)]
-            struct Args {
-                user: Address,
-                cid: Cid,
-                perm: Permission,
-            }
-            serde_json::to_vec(&Args { user, cid, perm }).unwrap()
-        };
-        self.call("has_perm", args, gas_left().saturating_sub(10_000))
-    }
-    pub fn has_approved_share(&self, cid: Cid, from_cid: Cid) -> bool {
-        let args = {
-            #[derive(Serialize)]
-            struct Args {
-                cid: Cid,
-                from_cid: Cid,
-            }
-            serde_json::to_vec(&Args { cid, from_cid }).unwrap()
-        };
-        self.call("has_approved_share", args, gas_left().saturating_sub(10_000))
-    }
-
-    fn call<R>(&self, method_name: &str, args: Vec<u8>, gas_limit: Gas) -> R
-    where
-        for<'a> R: Deserialize<'a>,
-    {
-        let call = ContractCall {
-            contract_address: self.address.clone(),
-            method_name: method_name.to_string(),
-            args,
-            read_only: true,
-            gas_limit,
-        };
-
-        match call_contract(&call) {
-            Ok(res) => {
-                if res.is_empty() {
-                    panic!("The external contract returned the empty result");
-                } else {
-                    let res: Result<R, serde_json::Error> = serde_json::from_slice(&res);
-                    // l1x_sdk::msg(&format!("Returned by the external contract: {:?}", res));
-
-                    res.expect("Can't deserialize external contract's return value")
-                }
-            }
-            Err(e) => {
-                panic!("Got the error when called contract {}, {:?}", self.address.clone(), e);
-            }
-        }
-    }
+#[contract_client(AccessControlContract)]
+pub trait AccessControl {
+    fn permissions(&self, user: Address, cid: Cid) -> Vec<Permission>;
+    fn has_perm(&self, user: Address, cid: Cid, perm: Permission) -> bool;
+    fn has_approved_share(&self, cid: Cid, from_cid: Cid) -> bool;
 }
\ No newline at end of file
@@ -1,24 +1,78 @@
 use borsh::BorshDeserialize;
 use borsh::BorshSerialize;
 use l1x_sdk::contract;
-
-use l1x_sdk::types::U64;
+use l1x_sdk::store::LookupMap;
+use l1x_sdk::types::{Address, U64};
+use l1x_sdk::{caller_address, contract_owner_address, emit_event_experimental};
+use serde::Serialize;
 
 const STORAGE_CONTRACT_KEY: &[u8] = b"STATE";
+const STORAGE_COUNTERS_KEY: &[u8] = b"COUNTERS";
+const STORAGE_STATE_HEADER_KEY: &[u8] = b"STATE-HEADER";
+
+/// The single counter layout without a state header
+const LEGACY_STATE_VERSION: u32 = 1;
+const STATE_VERSION: u32 = 2;
 
-#[derive(BorshSerialize)]
+/// Emitted on each counter change
+#[derive(BorshSerialize, Serialize)]
 struct Event {
     name: String,
+    address: Address,
+    old_value: U64,
+    new_value: U64,
+}
+
+impl Event {
+    fn emit(name: &str, address: Address, old_value: u64, new_value: u64) {
+        let event = Self {
+            name: name.to_string(),
+            address,
+            old_value: old_value.into(),
+            new_value: new_value.into(),
+        };
+        l1x_sdk::msg(&serde_json::to_string(&event).unwrap_or_default());
+        emit_event_experimental(event);
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct Contract {
+struct StateHeader {
+    version: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct ContractV1 {
     counter: U64,
 }
 
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Contract {
+    counters: LookupMap<Address, u64>,
+}
+
 #[contract]
 impl Contract {
+    fn state_version() -> u32 {
+        match l1x_sdk::storage_read(STORAGE_STATE_HEADER_KEY) {
+            Some(bytes) => StateHeader::try_from_slice(&bytes).unwrap().version,
+            None => LEGACY_STATE_VERSION,
+        }
+    }
+
+    fn save_state_version(version: u32) {
+        l1x_sdk::storage_write(
+            STORAGE_STATE_HEADER_KEY,
+            &StateHeader { version }.try_to_vec().unwrap(),
+        );
+    }
+
     fn load() -> Self {
+        assert_eq!(
+            Self::state_version(),
+            STATE_VERSION,
+            "The contract state is outdated, call `migrate` first"
+        );
         match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
             Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
             None => panic!("The contract isn't initialized"),
@@ -29,33 +83,102 @@ impl Contract {
         l1x_sdk::storage_write(STORAGE_CONTRACT_KEY, &self.try_to_vec().unwrap());
     }
 
+    fn counter_of(&self, address: &Address) -> u64 {
+        self.counters.get(address).copied().unwrap_or_default()
+    }
+
+    /// Adds `n` to the counter of `address` and returns the old value
+    fn increment(&mut self, address: Address, n: u64) -> u64 {
+        let old = self.counter_of(&address);
+        let new = old.checked_add(n).expect("Counter overflowed");
+        self.counters.insert(address, new);
+
+        Event::emit("inc_counter", address, old, new);
+
+        old
+    }
+
     pub fn new() {
         let mut state = Self {
-            counter: 0u64.into(),
+            counters: LookupMap::new(STORAGE_COUNTERS_KEY.to_vec()),
         };
 
-        state.save()
+        state.save();
+        Self::save_state_version(STATE_VERSION);
+    }
+
+    /// Moves the single counter of a contract initialized before the per-address counters to the owner's counter
+    pub fn migrate() {
+        let owner = contract_owner_address();
+        assert_eq!(
+            caller_address(),
+            owner,
+            "Only the contract owner can call this method"
+        );
+        assert_eq!(
+            Self::state_version(),
+            LEGACY_STATE_VERSION,
+            "The contract state is already migrated"
+        );
+
+        let legacy = match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => ContractV1::try_from_slice(&bytes).unwrap(),
+            None => panic!("The contract isn't initialized"),
+        };
+
+        let mut state = Self {
+            counters: LookupMap::new(STORAGE_COUNTERS_KEY.to_vec()),
+        };
+        state.counters.insert(owner, legacy.counter.0);
+
+        state.save();
+        Self::save_state_version(STATE_VERSION);
+
+        l1x_sdk::msg(&format!(
+            "The contract state has been migrated to version {}",
+            STATE_VERSION
+        ));
     }
 
-    pub fn set_counter(value: U64) -> U64 {
+    pub fn get_state_version() -> u32 {
+        Self::state_version()
+    }
+
+    pub fn set_counter(address: Address, value: U64) -> U64 {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+
         let mut state = Self::load();
-        let old = state.counter;
-        state.counter = value;
+        let old = state.counter_of(&address);
+        state.counters.insert(address, value.0);
+
+        Event::emit("set_counter", address, old, value.0);
+
         state.save();
 
-        old
+        old.into()
     }
 
     pub fn inc_counter() -> U64 {
         let mut state = Self::load();
-        let old = state.counter;
-        state.counter.0 += 1;
+        let old = state.increment(caller_address(), 1);
         state.save();
 
-        old
+        old.into()
+    }
+
+    pub fn inc_counter_by(n: U64) -> U64 {
+        let mut state = Self::load();
+        let old = state.increment(caller_address(), n.0);
+        state.save();
+
+        old.into()
     }
 
-    pub fn get_counter() -> U64 {
-        Self::load().counter
+    pub fn get_counter(address: Address) -> U64 {
+        Self::load().counter_of(&address).into()
     }
 }
@@ -9,6 +9,12 @@ use serde::{Deserialize, Serialize};
 const STORAGE_CONTRACT_KEY: &[u8; 6] = b"l1x-ft";
 const STORAGE_BALANCES_KEY: &[u8; 8] = b"balances";
 const STORAGE_ALLOWANCES_KEY: &[u8; 9] = b"allowance";
+const STORAGE_AUTHORIZED_CALLERS_KEY: &[u8; 10] = b"authorized";
+const STORAGE_STATE_HEADER_KEY: &[u8; 12] = b"state-header";
+
+/// The contract data layout without a state header
+const LEGACY_STATE_VERSION: u32 = 1;
+const STATE_VERSION: u32 = 2;
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct FTMetadata {
@@ -68,7 +74,12 @@ enum AllowanceUpdateOp {
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct L1xFtErc20 {
+struct StateHeader {
+    version: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct L1xFtErc20V1 {
     metadata: FTMetadata,
     balances: LookupMap<Address, u128>,
     allowances: LookupMap<Address, FTAllowance>,
@@ -76,6 +87,15 @@ pub struct L1xFtErc20 {
     authorized_callers: BTreeSet<Address>,
 }
 
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct L1xFtErc20 {
+    metadata: FTMetadata,
+    balances: LookupMap<Address, u128>,
+    allowances: LookupMap<Address, FTAllowance>,
+    total_supply: u128,
+    authorized_callers: LookupMap<Address, bool>,
+}
+
 #[contract]
 impl L1xFtErc20 {
     pub fn new(metadata: FTMetadata, account_ids: Vec<Address>, amounts: Vec<U128>) {
@@ -96,10 +116,55 @@ impl L1xFtErc20 {
             balances: LookupMap::new(STORAGE_BALANCES_KEY.to_vec()),
             allowances: LookupMap::new(STORAGE_ALLOWANCES_KEY.to_vec()),
             total_supply: Default::default(),
-            authorized_callers: BTreeSet::from([contract_owner_address()]),
+            authorized_callers: LookupMap::new(STORAGE_AUTHORIZED_CALLERS_KEY.to_vec()),
         };
+        contract
+            .authorized_callers
+            .insert(contract_owner_address(), true);
         contract.initialize_balance_holders(account_ids, amounts);
         contract.save();
+        Self::save_state_version(STATE_VERSION);
+    }
+
+    pub fn migrate() {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the owner can call this function"
+        );
+        assert_eq!(
+            Self::state_version(),
+            LEGACY_STATE_VERSION,
+            "The contract state is already migrated"
+        );
+
+        let legacy = match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => L1xFtErc20V1::try_from_slice(&bytes).unwrap(),
+            None => panic!("The contract isn't initialized"),
+        };
+
+        let mut contract = Self {
+            metadata: legacy.metadata,
+            balances: legacy.balances,
+            allowances: legacy.allowances,
+            total_supply: legacy.total_supply,
+            authorized_callers: LookupMap::new(STORAGE_AUTHORIZED_CALLERS_KEY.to_vec()),
+        };
+        for authorized_caller in legacy.authorized_callers {
+            contract.authorized_callers.insert(authorized_caller, true);
+        }
+
+        contract.save();
+        Self::save_state_version(STATE_VERSION);
+
+        l1x_sdk::msg(&format!(
+            "The contract state has been migrated to version {}",
+            STATE_VERSION
+        ));
+    }
+
+    pub fn ft_state_version() -> u32 {
+        Self::state_version()
     }
 
     fn initialize_balance_holders(&mut self, account_ids: Vec<Address>, amounts: Vec<U128>) {
@@ -138,10 +203,10 @@ impl L1xFtErc20 {
             "Authorized caller can be added by contract owner only"
         );
         assert!(
-            !contract.authorized_callers.contains(&authorized_caller),
+            !contract.authorized_callers.contains_key(&authorized_caller),
             "This address is already an authorized caller"
         );
-        contract.authorized_callers.insert(authorized_caller);
+        contract.authorized_callers.insert(authorized_caller, true);
         l1x_sdk::msg(&format!(
             "Authorized caller: {:?} has been added successfully",
             authorized_caller
@@ -177,7 +242,7 @@ impl L1xFtErc20 {
     pub fn ft_mint(recipient_id: Address, amount: U128) {
         let mut contract = Self::load();
         assert!(
-            contract.authorized_callers.contains(&caller_address()),
+            contract.authorized_callers.contains_key(&caller_address()),
             "Only authorized caller can mint tokens"
         );
         assert_ne!(amount.0, 0, "Amount should be greater than 0");
@@ -364,7 +429,26 @@ impl L1xFtErc20 {
         );
     }
 
+    fn state_version() -> u32 {
+        match l1x_sdk::storage_read(STORAGE_STATE_HEADER_KEY) {
+            Some(bytes) => StateHeader::try_from_slice(&bytes).unwrap().version,
+            None => LEGACY_STATE_VERSION,
+        }
+    }
+
+    fn save_state_version(version: u32) {
+        l1x_sdk::storage_write(
+            STORAGE_STATE_HEADER_KEY,
+            &StateHeader { version }.try_to_vec().unwrap(),
+        );
+    }
+
     fn load() -> Self {
+        assert_eq!(
+            Self::state_version(),
+            STATE_VERSION,
+            "The contract state is outdated, call `migrate` first"
+        );
         match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
             Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
             None => panic!("The contract isn't initialized"),
@@ -4,6 +4,12 @@ use std::str;
 #[derive(Debug)]
 pub struct Base64Data(Vec<u8>);
 
+impl Base64Data {
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
 impl<'de> Deserialize<'de> for Base64Data {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         struct Vis;
@@ -0,0 +1,421 @@
+use std::collections::BTreeSet;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_sdk::{
+    block_timestamp, caller_address, contract_instance_address, emit_event_experimental,
+    types::{Address, U128, U64},
+};
+use serde::Serialize;
+
+use crate::NftContract;
+
+pub type OfferId = u64;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum OfferStatus {
+    Open,
+    Accepted,
+    Cancelled,
+}
+
+/// A bundle swap between `maker` and `counterparty`.
+///
+/// The `give` tokens are held by the contract instance until the offer is accepted or cancelled.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+pub struct Offer {
+    maker: Address,
+    counterparty: Address,
+    give_ids: Vec<U128>,
+    give_amounts: Vec<U128>,
+    want_ids: Vec<U128>,
+    want_amounts: Vec<U128>,
+    /// The offer can't be accepted when `block_timestamp()` reaches `expiry`
+    expiry: U128,
+    status: OfferStatus,
+}
+
+#[derive(Debug, BorshSerialize, Serialize, Clone)]
+enum OfferEvent {
+    Created {
+        offer_id: U64,
+        maker: Address,
+        counterparty: Address,
+    },
+    Accepted {
+        offer_id: U64,
+    },
+    Cancelled {
+        offer_id: U64,
+    },
+}
+
+impl OfferEvent {
+    fn emit(self) {
+        l1x_sdk::msg(&serde_json::to_string(&self).unwrap_or_default());
+        emit_event_experimental(self);
+    }
+}
+
+impl NftContract {
+    pub(crate) fn create_offer(
+        &mut self,
+        counterparty: Address,
+        give_ids: Vec<U128>,
+        give_amounts: Vec<U128>,
+        want_ids: Vec<U128>,
+        want_amounts: Vec<U128>,
+        expiry: U128,
+    ) -> OfferId {
+        let maker = caller_address();
+        Self::assert_offer_tokens("Give", &give_ids, &give_amounts);
+        Self::assert_offer_tokens("Want", &want_ids, &want_amounts);
+        assert!(!give_ids.is_empty(), "The offer should give some tokens");
+        Self::assert_zero_address(&counterparty);
+        assert_ne!(maker, counterparty, "Can't create an offer to yourself");
+        assert!(
+            expiry.0 > block_timestamp(),
+            "The offer expiry should be in the future"
+        );
+
+        // Lock the offered tokens on the contract instance balance
+        self.internal_batch_transfer(
+            maker,
+            contract_instance_address(),
+            give_ids.clone(),
+            give_amounts.clone(),
+        );
+
+        let offer_id = self.next_offer_id;
+        self.next_offer_id = offer_id.checked_add(1).expect("Integer overflow");
+        self.offers.insert(
+            offer_id,
+            Offer {
+                maker,
+                counterparty,
+                give_ids,
+                give_amounts,
+                want_ids,
+                want_amounts,
+                expiry,
+                status: OfferStatus::Open,
+            },
+        );
+
+        OfferEvent::Created {
+            offer_id: offer_id.into(),
+            maker,
+            counterparty,
+        }
+        .emit();
+
+        offer_id
+    }
+
+    pub(crate) fn accept_offer(&mut self, offer_id: OfferId) {
+        let offer = self.open_offer(offer_id);
+        let caller_id = caller_address();
+        assert_eq!(
+            caller_id, offer.counterparty,
+            "Only the counterparty can accept the offer"
+        );
+        assert!(
+            block_timestamp() < offer.expiry.0,
+            "The offer {} is expired",
+            offer_id
+        );
+
+        // Both legs are settled in this call, any failed leg reverts the swap
+        if !offer.want_ids.is_empty() {
+            self.internal_batch_transfer(
+                offer.counterparty,
+                offer.maker,
+                offer.want_ids.clone(),
+                offer.want_amounts.clone(),
+            );
+        }
+        self.internal_batch_transfer(
+            contract_instance_address(),
+            offer.counterparty,
+            offer.give_ids.clone(),
+            offer.give_amounts.clone(),
+        );
+
+        self.set_offer_status(offer_id, OfferStatus::Accepted);
+
+        OfferEvent::Accepted {
+            offer_id: offer_id.into(),
+        }
+        .emit();
+    }
+
+    pub(crate) fn cancel_offer(&mut self, offer_id: OfferId) {
+        let offer = self.open_offer(offer_id);
+        let caller_id = caller_address();
+        assert!(
+            caller_id == offer.maker || block_timestamp() >= offer.expiry.0,
+            "Only the maker can cancel the offer before it expires"
+        );
+
+        // Return the locked tokens to the maker
+        self.internal_batch_transfer(
+            contract_instance_address(),
+            offer.maker,
+            offer.give_ids.clone(),
+            offer.give_amounts.clone(),
+        );
+
+        self.set_offer_status(offer_id, OfferStatus::Cancelled);
+
+        OfferEvent::Cancelled {
+            offer_id: offer_id.into(),
+        }
+        .emit();
+    }
+
+    /// Calls the acceptance hooks of the parties that received tokens when the offer was settled
+    pub(crate) fn notify_offer_settled(&self, offer_id: OfferId) {
+        let offer = self.offer(offer_id).expect("Can't find the offer");
+        let escrow = contract_instance_address();
+
+        match offer.status {
+            OfferStatus::Accepted => {
+                if !offer.want_ids.is_empty() {
+                    self.notify_batch_received(
+                        offer.counterparty,
+                        offer.maker,
+                        offer.want_ids,
+                        offer.want_amounts,
+                        Vec::new(),
+                    );
+                }
+                self.notify_batch_received(
+                    escrow,
+                    offer.counterparty,
+                    offer.give_ids,
+                    offer.give_amounts,
+                    Vec::new(),
+                );
+            }
+            OfferStatus::Cancelled => self.notify_batch_received(
+                escrow,
+                offer.maker,
+                offer.give_ids,
+                offer.give_amounts,
+                Vec::new(),
+            ),
+            OfferStatus::Open => (),
+        }
+    }
+
+    pub(crate) fn offer(&self, offer_id: OfferId) -> Option<Offer> {
+        self.offers.get(&offer_id).cloned()
+    }
+
+    fn open_offer(&self, offer_id: OfferId) -> Offer {
+        let offer = self
+            .offer(offer_id)
+            .unwrap_or_else(|| panic!("Offer {} doesn't exist", offer_id));
+        assert_eq!(
+            offer.status,
+            OfferStatus::Open,
+            "Offer {} is not open",
+            offer_id
+        );
+        offer
+    }
+
+    /// A zero amount or a repeated id would make the offer impossible to accept
+    fn assert_offer_tokens(side: &str, ids: &[U128], amounts: &[U128]) {
+        assert_eq!(
+            ids.len(),
+            amounts.len(),
+            "{} ids and amounts must be same length",
+            side
+        );
+        assert!(
+            amounts.iter().all(|amount| amount.0 > 0),
+            "{} amounts should be greater than 0",
+            side
+        );
+        let unique_ids = ids.iter().map(|id| id.0).collect::<BTreeSet<_>>();
+        assert_eq!(unique_ids.len(), ids.len(), "{} ids must be unique", side);
+    }
+
+    fn set_offer_status(&mut self, offer_id: OfferId, status: OfferStatus) {
+        self.offers
+            .get_mut(&offer_id)
+            .expect("Can't find the offer")
+            .status = status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use l1x_sdk::types::{Address, U64};
+
+    use super::OfferStatus;
+    use crate::test_host::{self, set_block_timestamp, set_caller};
+    use crate::{NFTMetadata, NftContract};
+
+    const GIVE_ID: u128 = 1;
+    const WANT_ID: u128 = 2;
+    const EXPIRY: u128 = 1_000;
+
+    fn maker() -> Address {
+        Address::from([1; 20])
+    }
+
+    fn counterparty() -> Address {
+        Address::from([2; 20])
+    }
+
+    /// Mints 10 tokens of `GIVE_ID` to the maker and 5 tokens of `WANT_ID` to the counterparty
+    fn setup() {
+        set_block_timestamp(100);
+        set_caller(test_host::owner());
+        NftContract::new(NFTMetadata {
+            name: "Bundles".to_string(),
+            icon: None,
+            uri: "https://example.com/".to_string(),
+        });
+        NftContract::nft_mint_id(maker(), GIVE_ID.into(), 10u128.into());
+        NftContract::nft_mint_id(counterparty(), WANT_ID.into(), 5u128.into());
+    }
+
+    fn balance(owner: Address, id: u128) -> u128 {
+        NftContract::nft_balance_of(owner, id.into()).0
+    }
+
+    /// Offers 4 tokens of `GIVE_ID` for `want_amount` tokens of `WANT_ID`
+    fn create_offer(want_amount: u128) -> U64 {
+        set_caller(maker());
+        NftContract::nft_create_offer(
+            counterparty(),
+            vec![GIVE_ID.into()],
+            vec![4u128.into()],
+            vec![WANT_ID.into()],
+            vec![want_amount.into()],
+            EXPIRY.into(),
+        )
+    }
+
+    fn status(offer_id: U64) -> OfferStatus {
+        NftContract::nft_offer(offer_id)
+            .expect("The offer exists")
+            .status
+    }
+
+    #[test]
+    fn create_locks_the_given_tokens() {
+        setup();
+        let offer_id = create_offer(3);
+
+        assert_eq!(status(offer_id), OfferStatus::Open);
+        assert_eq!(balance(maker(), GIVE_ID), 6);
+        assert_eq!(balance(test_host::instance(), GIVE_ID), 4);
+    }
+
+    #[test]
+    fn accept_swaps_the_bundles() {
+        setup();
+        let offer_id = create_offer(3);
+
+        set_caller(counterparty());
+        NftContract::nft_accept_offer(offer_id);
+
+        assert_eq!(status(offer_id), OfferStatus::Accepted);
+        assert_eq!(balance(maker(), GIVE_ID), 6);
+        assert_eq!(balance(maker(), WANT_ID), 3);
+        assert_eq!(balance(counterparty(), GIVE_ID), 4);
+        assert_eq!(balance(counterparty(), WANT_ID), 2);
+        assert_eq!(balance(test_host::instance(), GIVE_ID), 0);
+    }
+
+    #[test]
+    fn cancel_returns_the_locked_tokens() {
+        setup();
+        let offer_id = create_offer(3);
+
+        NftContract::nft_cancel_offer(offer_id);
+
+        assert_eq!(status(offer_id), OfferStatus::Cancelled);
+        assert_eq!(balance(maker(), GIVE_ID), 10);
+        assert_eq!(balance(test_host::instance(), GIVE_ID), 0);
+    }
+
+    #[test]
+    fn anyone_can_cancel_an_expired_offer() {
+        setup();
+        let offer_id = create_offer(3);
+
+        set_block_timestamp(EXPIRY);
+        set_caller(Address::from([3; 20]));
+        NftContract::nft_cancel_offer(offer_id);
+
+        assert_eq!(status(offer_id), OfferStatus::Cancelled);
+        assert_eq!(balance(maker(), GIVE_ID), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the maker can cancel the offer before it expires")]
+    fn only_the_maker_can_cancel_an_open_offer() {
+        setup();
+        let offer_id = create_offer(3);
+
+        set_caller(counterparty());
+        NftContract::nft_cancel_offer(offer_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the counterparty can accept the offer")]
+    fn only_the_counterparty_can_accept() {
+        setup();
+        let offer_id = create_offer(3);
+
+        NftContract::nft_accept_offer(offer_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "is expired")]
+    fn expired_offer_can_not_be_accepted() {
+        setup();
+        let offer_id = create_offer(3);
+
+        set_block_timestamp(EXPIRY);
+        set_caller(counterparty());
+        NftContract::nft_accept_offer(offer_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not open")]
+    fn settled_offer_can_not_be_accepted_again() {
+        setup();
+        let offer_id = create_offer(3);
+
+        set_caller(counterparty());
+        NftContract::nft_accept_offer(offer_id);
+        NftContract::nft_accept_offer(offer_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Want amounts should be greater than 0")]
+    fn zero_amounts_are_rejected() {
+        setup();
+        create_offer(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Give ids must be unique")]
+    fn repeated_ids_are_rejected() {
+        setup();
+        set_caller(maker());
+        NftContract::nft_create_offer(
+            counterparty(),
+            vec![GIVE_ID.into(), GIVE_ID.into()],
+            vec![2u128.into(), 2u128.into()],
+            Vec::new(),
+            Vec::new(),
+            EXPIRY.into(),
+        );
+    }
+}
@@ -1,25 +1,69 @@
 mod base64_data;
+mod escrow;
+mod receiver;
+#[cfg(test)]
+mod test_host;
 
 use std::collections::BTreeMap;
 
 use base64_data::Base64Data;
 use borsh::{BorshDeserialize, BorshSerialize};
+use escrow::{Offer, OfferId};
 use l1x_sdk::{
     caller_address, contract, contract_owner_address, emit_event_experimental,
-    store::LookupMap,
-    types::{Address, U128},
+    store::{LookupMap, Vector},
+    types::{Address, U128, U64},
 };
 use serde::{Deserialize, Serialize};
 
 /// Key for the storage of the contract data.
 const STORAGE_CONTRACT_KEY: &[u8] = b"state";
 
+/// Key for the storage of the state header.
+const STORAGE_STATE_HEADER_KEY: &[u8] = b"state-header";
+
+/// Layout of the contract data before the state header was introduced.
+const LEGACY_STATE_VERSION: u32 = 1;
+
+/// Current layout of the contract data.
+const STATE_VERSION: u32 = 2;
+
+/// Key for the storage of the balance data of `LEGACY_STATE_VERSION`.
+const STORAGE_LEGACY_BALANCE_OF_KEY: &[u8] = b"balances";
+
 /// Key for the storage of the balance data.
-const STORAGE_BALANCE_OF_KEY: &[u8] = b"balances";
+const STORAGE_BALANCE_OF_KEY: &[u8] = b"id-balances";
+
+/// Key for the storage of the index of token ids held by a user.
+const STORAGE_OWNED_IDS_KEY: &[u8] = b"owned-ids";
+
+/// Key suffix for the storage of token ids held by a user
+const STORAGE_OWNED_IDS_VEC_KEY: &[u8] = b"ids";
 
 /// Key for the storage of the approval status data.
 const STORAGE_IS_APPROVED_FOR_ALL_KEY: &[u8] = b"approved-all";
 
+/// Key for the storage of the minters.
+const STORAGE_MINTERS_KEY: &[u8] = b"minters";
+
+/// Key for the storage of the registered token types.
+const STORAGE_TOKEN_TYPES_KEY: &[u8] = b"types";
+
+/// Key for the storage of the total supply per token id.
+const STORAGE_TOTAL_SUPPLY_KEY: &[u8] = b"supply";
+
+/// Key for the storage of the URI overrides per token id.
+const STORAGE_TOKEN_URIS_KEY: &[u8] = b"uris";
+
+/// Key for the storage of the escrow offers.
+const STORAGE_OFFERS_KEY: &[u8] = b"offers";
+
+/// Key for the storage of the recipients registered for the acceptance hooks.
+const STORAGE_RECEIVERS_KEY: &[u8] = b"receivers";
+
+/// Placeholder replaced by the hex token id in URIs
+const URI_ID_PLACEHOLDER: &str = "{id}";
+
 #[derive(BorshSerialize, BorshDeserialize, Deserialize)]
 pub struct NFTMetadata {
     name: String,
@@ -27,6 +71,20 @@ pub struct NFTMetadata {
     uri: String,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy)]
+struct BalanceInfo {
+    amount: u128,
+    /// Position of the token id in the holder's `owned_ids` vector
+    id_idx: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+pub struct TokenType {
+    /// The maximum amount of the token that can exist, `None` if the supply is not capped
+    max_supply: Option<U128>,
+    fungible: bool,
+}
+
 #[derive(Debug, BorshSerialize, Serialize, Clone)]
 enum Erc1155Event {
     TransferSingle {
@@ -48,15 +106,47 @@ enum Erc1155Event {
         operator: Address,
         approved: bool,
     },
+    /// Named after the ERC-1155 `URI` event
+    #[allow(clippy::upper_case_acronyms)]
+    URI { value: String, id: U128 },
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct NftContract {
+struct StateHeader {
+    version: u32,
+}
+
+/// Contract data of `LEGACY_STATE_VERSION`. Used only by `migrate`
+#[derive(BorshDeserialize)]
+struct NftContractV1 {
     metadata: NFTMetadata,
+    /// Left in storage for `nft_migrate_balances`
+    #[allow(dead_code)]
     balance_of: LookupMap<Address, BTreeMap<u128, u128>>,
     is_approved_for_all: LookupMap<Address, BTreeMap<Address, bool>>,
 }
 
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct NftContract {
+    metadata: NFTMetadata,
+    balance_of: LookupMap<(Address, u128), BalanceInfo>,
+    owned_ids: LookupMap<Address, Vector<u128>>,
+    is_approved_for_all: LookupMap<Address, BTreeMap<Address, bool>>,
+    /// Addresses allowed to mint in addition to the contract owner
+    minters: LookupMap<Address, bool>,
+    token_types: LookupMap<u128, TokenType>,
+    total_supply: LookupMap<u128, u128>,
+    /// URI used instead of the metadata URI for the token id
+    token_uris: LookupMap<u128, String>,
+    offers: LookupMap<OfferId, Offer>,
+    next_offer_id: OfferId,
+    /// Recipients whose acceptance hooks are called when they receive tokens
+    receivers: LookupMap<Address, bool>,
+    /// `true` from `migrate` until `nft_finish_balance_migration`. The total supply doesn't count the legacy
+    /// balances that are not moved yet, so minting and creating token types are blocked meanwhile
+    migrating_balances: bool,
+}
+
 #[contract]
 impl NftContract {
     pub fn new(metadata: NFTMetadata) {
@@ -66,11 +156,100 @@ impl NftContract {
         let mut contract = Self {
             metadata,
             balance_of: LookupMap::new(STORAGE_BALANCE_OF_KEY.to_vec()),
+            owned_ids: LookupMap::new(STORAGE_OWNED_IDS_KEY.to_vec()),
             is_approved_for_all: LookupMap::new(STORAGE_IS_APPROVED_FOR_ALL_KEY.to_vec()),
+            minters: LookupMap::new(STORAGE_MINTERS_KEY.to_vec()),
+            token_types: LookupMap::new(STORAGE_TOKEN_TYPES_KEY.to_vec()),
+            total_supply: LookupMap::new(STORAGE_TOTAL_SUPPLY_KEY.to_vec()),
+            token_uris: LookupMap::new(STORAGE_TOKEN_URIS_KEY.to_vec()),
+            offers: LookupMap::new(STORAGE_OFFERS_KEY.to_vec()),
+            next_offer_id: 0,
+            receivers: LookupMap::new(STORAGE_RECEIVERS_KEY.to_vec()),
+            migrating_balances: false,
+        };
+        contract.save();
+        Self::save_state_version(STATE_VERSION);
+    }
+
+    /// Converts the contract data of an older layout in place.
+    ///
+    /// The legacy balances can't be iterated, they are moved by `nft_migrate_balances`. Minting and creating
+    /// token types are blocked until `nft_finish_balance_migration`
+    pub fn migrate() {
+        Self::assert_caller_is_owner();
+        assert_eq!(
+            Self::state_version(),
+            LEGACY_STATE_VERSION,
+            "The contract state is already migrated"
+        );
+
+        let legacy = match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => NftContractV1::try_from_slice(&bytes).unwrap(),
+            None => panic!("The contract isn't initialized"),
+        };
+
+        let mut contract = Self {
+            metadata: legacy.metadata,
+            balance_of: LookupMap::new(STORAGE_BALANCE_OF_KEY.to_vec()),
+            owned_ids: LookupMap::new(STORAGE_OWNED_IDS_KEY.to_vec()),
+            is_approved_for_all: legacy.is_approved_for_all,
+            minters: LookupMap::new(STORAGE_MINTERS_KEY.to_vec()),
+            token_types: LookupMap::new(STORAGE_TOKEN_TYPES_KEY.to_vec()),
+            total_supply: LookupMap::new(STORAGE_TOTAL_SUPPLY_KEY.to_vec()),
+            token_uris: LookupMap::new(STORAGE_TOKEN_URIS_KEY.to_vec()),
+            offers: LookupMap::new(STORAGE_OFFERS_KEY.to_vec()),
+            next_offer_id: 0,
+            receivers: LookupMap::new(STORAGE_RECEIVERS_KEY.to_vec()),
+            migrating_balances: true,
         };
+
+        contract.save();
+        Self::save_state_version(STATE_VERSION);
+
+        l1x_sdk::msg(&format!(
+            "Migrated the contract state from version {} to {}",
+            LEGACY_STATE_VERSION, STATE_VERSION
+        ));
+    }
+
+    /// Moves the balances of `holders` from the legacy layout and counts them in the total supply.
+    /// Holders without legacy balances are skipped
+    pub fn nft_migrate_balances(holders: Vec<Address>) {
+        Self::assert_caller_is_owner();
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.migrate_balances(holders);
+
+        // Save the contract state
+        contract.save();
+    }
+
+    /// Marks the legacy balances as moved and unblocks minting and creating token types
+    pub fn nft_finish_balance_migration() {
+        Self::assert_caller_is_owner();
+        let mut contract = Self::load();
+
+        assert!(
+            contract.migrating_balances,
+            "The balances are already migrated"
+        );
+        contract.migrating_balances = false;
+        l1x_sdk::msg("The balance migration has been finished");
+
         contract.save();
     }
 
+    pub fn nft_is_migrating_balances() -> bool {
+        let contract = Self::load();
+        contract.migrating_balances
+    }
+
+    pub fn nft_state_version() -> u32 {
+        Self::state_version()
+    }
+
     pub fn nft_name() -> String {
         let contract = Self::load();
         contract.metadata.name
@@ -78,7 +257,49 @@ impl NftContract {
 
     pub fn nft_uri(id: U128) -> String {
         let contract = Self::load();
-        contract.metadata.uri + &id.0.to_string() + ".json"
+        contract.uri(id.into())
+    }
+
+    pub fn nft_set_uri(id: U128, uri: Option<String>) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.set_uri(id.into(), uri);
+
+        // Save the contract state
+        contract.save();
+    }
+
+    pub fn nft_create_type(
+        id: U128,
+        max_supply: Option<U128>,
+        uri_override: Option<String>,
+        fungible: bool,
+    ) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.create_type(id.into(), max_supply, uri_override, fungible);
+
+        // Save the contract state
+        contract.save();
+    }
+
+    pub fn nft_token_type(id: U128) -> Option<TokenType> {
+        let contract = Self::load();
+        contract.token_types.get(&id.0).cloned()
+    }
+
+    pub fn nft_total_supply(id: U128) -> U128 {
+        let contract = Self::load();
+        contract.total_supply(id.into()).into()
+    }
+
+    pub fn nft_exists(id: U128) -> bool {
+        let contract = Self::load();
+        contract.token_types.contains_key(&id.0) || contract.total_supply(id.into()) > 0
     }
 
     pub fn nft_icon() -> Option<String> {
@@ -95,6 +316,21 @@ impl NftContract {
 
         // Save the contract state
         contract.save();
+
+        contract.notify_received(Address::from([0; 20]), to, id, amount, Vec::new());
+    }
+
+    pub fn nft_mint_batch(to: Address, ids: Vec<U128>, amounts: Vec<U128>) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.mint_batch(to, ids.clone(), amounts.clone());
+
+        // Save the contract state
+        contract.save();
+
+        contract.notify_batch_received(Address::from([0; 20]), to, ids, amounts, Vec::new());
     }
 
     pub fn nft_burn(account: Address, id: U128, amount: U128) {
@@ -108,6 +344,46 @@ impl NftContract {
         contract.save();
     }
 
+    pub fn nft_burn_batch(account: Address, ids: Vec<U128>, amounts: Vec<U128>) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.burn_batch(account, ids, amounts);
+
+        // Save the contract state
+        contract.save();
+    }
+
+    pub fn nft_add_minter(minter: Address) {
+        Self::assert_caller_is_owner();
+        let mut contract = Self::load();
+
+        contract.minters.insert(minter, true);
+        l1x_sdk::msg(&format!("Minter {} has been added", minter));
+
+        contract.save();
+    }
+
+    pub fn nft_remove_minter(minter: Address) {
+        Self::assert_caller_is_owner();
+        let mut contract = Self::load();
+
+        assert!(
+            contract.minters.remove(minter).is_some(),
+            "{} is not a minter",
+            minter
+        );
+        l1x_sdk::msg(&format!("Minter {} has been removed", minter));
+
+        contract.save();
+    }
+
+    pub fn nft_is_minter(account: Address) -> bool {
+        let contract = Self::load();
+        contract.is_minter(&account)
+    }
+
     pub fn nft_set_approval_for_all(operator: Address, approved: bool) {
         // load the contract storage state
         let mut contract = Self::load();
@@ -127,12 +403,25 @@ impl NftContract {
         contract.balance_of(owner, id.into()).into()
     }
 
-    pub fn nft_owned_tokens(owner: Address) -> Vec<(U128, U128)> {
+    pub fn nft_owned_tokens(
+        owner: Address,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<(U128, U128)> {
         // load the contract storage state
         let contract = Self::load();
 
         // Call the internal implementation
-        contract.owned_tokens(owner)
+        contract.owned_tokens(owner, from.unwrap_or_default(), limit)
+    }
+
+    pub fn nft_owned_tokens_count(owner: Address) -> u32 {
+        let contract = Self::load();
+        contract
+            .owned_ids
+            .get(&owner)
+            .map(|ids| ids.len())
+            .unwrap_or_default()
     }
 
     pub fn nft_safe_transfer_from(
@@ -140,7 +429,7 @@ impl NftContract {
         to: Address,
         id: U128,
         amount: U128,
-        #[allow(unused_variables)] calldata: Base64Data,
+        calldata: Base64Data,
     ) {
         // load the contract storage state
         let mut contract = Self::load();
@@ -150,6 +439,8 @@ impl NftContract {
 
         // Save the contract state
         contract.save();
+
+        contract.notify_received(from, to, id, amount, calldata.into_vec());
     }
 
     pub fn nft_safe_batch_transfer_from(
@@ -157,16 +448,18 @@ impl NftContract {
         to: Address,
         ids: Vec<U128>,
         values: Vec<U128>,
-        #[allow(unused_variables)] calldata: Base64Data,
+        calldata: Base64Data,
     ) {
         // load the contract storage state
         let mut contract = Self::load();
 
         // Call the internal implementation
-        contract.safe_batch_transfer_from(from, to, ids, values);
+        contract.safe_batch_transfer_from(from, to, ids.clone(), values.clone());
 
         // Save the contract state
         contract.save();
+
+        contract.notify_batch_received(from, to, ids, values, calldata.into_vec());
     }
 
     pub fn nft_balance_of_batch(owners: Vec<Address>, ids: Vec<U128>) -> Vec<U128> {
@@ -179,6 +472,92 @@ impl NftContract {
         let contract = Self::load();
         contract.is_approved_for_all(&owner, &operator)
     }
+
+    /// Registers the caller for the acceptance hooks, or unregisters it if `enabled` is false
+    pub fn nft_set_receiver(enabled: bool) {
+        let mut contract = Self::load();
+
+        let receiver = caller_address();
+        if enabled {
+            contract.receivers.insert(receiver, true);
+        } else {
+            contract.receivers.remove(receiver);
+        }
+        l1x_sdk::msg(&format!(
+            "Receiver {} has been {}",
+            receiver,
+            if enabled {
+                "registered"
+            } else {
+                "unregistered"
+            }
+        ));
+
+        contract.save();
+    }
+
+    pub fn nft_is_receiver(account: Address) -> bool {
+        let contract = Self::load();
+        contract.receivers.contains_key(&account)
+    }
+
+    pub fn nft_create_offer(
+        counterparty: Address,
+        give_ids: Vec<U128>,
+        give_amounts: Vec<U128>,
+        want_ids: Vec<U128>,
+        want_amounts: Vec<U128>,
+        expiry: U128,
+    ) -> U64 {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        let offer_id = contract.create_offer(
+            counterparty,
+            give_ids,
+            give_amounts,
+            want_ids,
+            want_amounts,
+            expiry,
+        );
+
+        // Save the contract state
+        contract.save();
+
+        offer_id.into()
+    }
+
+    pub fn nft_accept_offer(offer_id: U64) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.accept_offer(offer_id.into());
+
+        // Save the contract state
+        contract.save();
+
+        contract.notify_offer_settled(offer_id.into());
+    }
+
+    pub fn nft_cancel_offer(offer_id: U64) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.cancel_offer(offer_id.into());
+
+        // Save the contract state
+        contract.save();
+
+        contract.notify_offer_settled(offer_id.into());
+    }
+
+    pub fn nft_offer(offer_id: U64) -> Option<Offer> {
+        let contract = Self::load();
+        contract.offer(offer_id.into())
+    }
 }
 
 impl NftContract {
@@ -190,42 +569,190 @@ impl NftContract {
         decrease: bool,
     ) {
         // Update the balances
-        if let Some(tokens) = self.balance_of.get_mut(&account) {
-            let mut found_zero_balance = false;
-            if let Some(balance) = tokens.get_mut(&id) {
-                let new_balance = if decrease {
-                    balance.checked_sub(amount).expect("Not enough fund")
-                } else {
-                    balance.checked_add(amount).expect("Integer overflow")
-                };
-
-                *balance = new_balance;
-
-                if new_balance == 0 {
-                    found_zero_balance = true;
-                }
+        if let Some(balance) = self.balance_of.get(&(account, id)).copied() {
+            let new_balance = if decrease {
+                balance.amount.checked_sub(amount).expect("Not enough fund")
+            } else {
+                balance
+                    .amount
+                    .checked_add(amount)
+                    .expect("Integer overflow")
+            };
+
+            if new_balance == 0 {
+                // Remove useless records
+                self.balance_of.remove((account, id));
+                self.internal_remove_owned_id(account, balance.id_idx);
             } else {
-                // No tokens case
-                assert!(!decrease, "No fund");
-                tokens.insert(id, amount);
+                self.balance_of.insert(
+                    (account, id),
+                    BalanceInfo {
+                        amount: new_balance,
+                        id_idx: balance.id_idx,
+                    },
+                );
+            }
+        } else {
+            // No tokens case
+            assert!(!decrease, "No fund");
+            if amount == 0 {
+                return;
             }
+            let id_idx = self.internal_add_owned_id(account, id);
+            self.balance_of
+                .insert((account, id), BalanceInfo { amount, id_idx });
+        };
+    }
+
+    fn internal_new_owned_ids_vec(&self, address: &Address) -> Vector<u128> {
+        Vector::<u128>::new([&address.to_vec(), STORAGE_OWNED_IDS_VEC_KEY].concat())
+    }
+
+    fn internal_add_owned_id(&mut self, account: Address, id: u128) -> u32 {
+        let owned_ids = if let Some(v) = self.owned_ids.get_mut(&account) {
+            v
+        } else {
+            let new_vec = self.internal_new_owned_ids_vec(&account);
+            self.owned_ids.insert(account, new_vec);
+            self.owned_ids
+                .get_mut(&account)
+                .expect("Can't get the just added Vector")
+        };
+
+        owned_ids.push(id);
+
+        owned_ids.len() - 1
+    }
+
+    fn internal_remove_owned_id(&mut self, account: Address, id_idx: u32) {
+        let owned_ids = self
+            .owned_ids
+            .get_mut(&account)
+            .expect("Can't find token ids of the holder");
+
+        let is_last = id_idx == owned_ids.len() - 1;
+        // 1. Removes the id from the Vector. The removed id is replaced by the last element in the Vector
+        owned_ids.swap_remove(id_idx);
+        // 2. If it is the last Vector element, additional work is not required
+        if !is_last {
+            // 3. Update the reference in balance_of because the id index in Vector has been changed
+            let swapped_id = *owned_ids
+                .get(id_idx)
+                .expect("Can't get the swapped token id");
+            let balance_ref = self
+                .balance_of
+                .get_mut(&(account, swapped_id))
+                .expect("Can't find a balance of the swapped token id");
+            balance_ref.id_idx = id_idx;
+        }
+    }
 
-            // Remove useless records
-            if found_zero_balance {
-                tokens.remove(&id);
+    fn internal_supply_update(&mut self, id: u128, amount: u128, decrease: bool) {
+        let supply = self.total_supply(id);
+        let new_supply = if decrease {
+            supply.checked_sub(amount).expect("Not enough supply")
+        } else {
+            supply.checked_add(amount).expect("Integer overflow")
+        };
+
+        if !decrease {
+            if let Some(max_supply) = self
+                .token_types
+                .get(&id)
+                .and_then(|token_type| token_type.max_supply)
+            {
+                assert!(
+                    new_supply <= max_supply.0,
+                    "Max supply of Id {} reached",
+                    id
+                );
             }
+        }
+
+        if new_supply == 0 {
+            self.total_supply.remove(id);
         } else {
-            // No any tokens case
-            assert!(!decrease, "No fund");
-            let mut new_map = BTreeMap::new();
-            new_map.insert(id, amount);
-            self.balance_of.insert(account, new_map);
+            self.total_supply.insert(id, new_supply);
+        }
+    }
+
+    fn create_type(
+        &mut self,
+        id: u128,
+        max_supply: Option<U128>,
+        uri_override: Option<String>,
+        fungible: bool,
+    ) {
+        self.assert_caller_is_minter();
+        self.assert_balances_migrated();
+        assert!(
+            !self.token_types.contains_key(&id),
+            "Token type {} already exists",
+            id
+        );
+        if !fungible {
+            assert_eq!(
+                max_supply.map(|max_supply| max_supply.0),
+                Some(1),
+                "Non-fungible token type must have the max supply 1"
+            );
+        }
+        if let Some(max_supply) = max_supply {
+            assert!(
+                self.total_supply(id) <= max_supply.0,
+                "Id {} is already minted above the max supply",
+                id
+            );
+        }
+
+        self.token_types.insert(
+            id,
+            TokenType {
+                max_supply,
+                fungible,
+            },
+        );
+        if uri_override.is_some() {
+            self.set_uri(id, uri_override);
+        }
+
+        l1x_sdk::msg(&format!("Token type {} has been created", id));
+    }
+
+    fn uri(&self, id: u128) -> String {
+        match self.token_uris.get(&id) {
+            Some(uri) => uri.replace(URI_ID_PLACEHOLDER, &format!("{:064x}", id)),
+            None if self.metadata.uri.contains(URI_ID_PLACEHOLDER) => self
+                .metadata
+                .uri
+                .replace(URI_ID_PLACEHOLDER, &format!("{:064x}", id)),
+            None => self.metadata.uri.clone() + &id.to_string() + ".json",
+        }
+    }
+
+    fn set_uri(&mut self, id: u128, uri: Option<String>) {
+        self.assert_caller_is_minter();
+
+        match uri {
+            Some(uri) => self.token_uris.insert(id, uri),
+            None => self.token_uris.remove(id),
+        };
+
+        let event = Erc1155Event::URI {
+            value: self.uri(id),
+            id: id.into(),
         };
+        // Emit the URI changed event
+        emit_event_experimental(event.clone());
+
+        l1x_sdk::msg(&serde_json::to_string(&event).unwrap_or_default());
     }
 
     fn mint_id_to(&mut self, to: Address, id: u128, amount: u128) {
-        Self::assert_caller_is_owner();
+        self.assert_caller_is_minter();
+        self.assert_balances_migrated();
 
+        self.internal_supply_update(id, amount, false);
         self.internal_balance_update(to, id, amount, false);
 
         let event = Erc1155Event::TransferSingle {
@@ -244,10 +771,39 @@ impl NftContract {
         emit_event_experimental(event);
     }
 
+    fn mint_batch(&mut self, to: Address, ids: Vec<U128>, amounts: Vec<U128>) {
+        assert_eq!(
+            ids.len(),
+            amounts.len(),
+            "Ids and amounts must be same length"
+        );
+        self.assert_caller_is_minter();
+        self.assert_balances_migrated();
+        Self::assert_zero_address(&to);
+
+        ids.iter().zip(amounts.iter()).for_each(|(id, amount)| {
+            self.internal_supply_update(id.0, amount.0, false);
+            self.internal_balance_update(to, id.0, amount.0, false);
+        });
+
+        let event = Erc1155Event::TransferBatch {
+            operator: caller_address(),
+            from: Address::from([0; 20]),
+            to,
+            ids,
+            values: amounts,
+        };
+        // Emit the Tokens minted event
+        emit_event_experimental(event.clone());
+
+        l1x_sdk::msg(&serde_json::to_string(&event).unwrap_or_default());
+    }
+
     fn burn(&mut self, account: Address, id: u128, amount: u128) {
-        Self::assert_caller_is_owner();
+        self.assert_caller_is_approved(&account);
 
         self.internal_balance_update(account, id, amount, true);
+        self.internal_supply_update(id, amount, true);
 
         let event = Erc1155Event::TransferSingle {
             operator: caller_address(),
@@ -266,6 +822,32 @@ impl NftContract {
         ));
     }
 
+    fn burn_batch(&mut self, account: Address, ids: Vec<U128>, amounts: Vec<U128>) {
+        assert_eq!(
+            ids.len(),
+            amounts.len(),
+            "Ids and amounts must be same length"
+        );
+        self.assert_caller_is_approved(&account);
+
+        ids.iter().zip(amounts.iter()).for_each(|(id, amount)| {
+            self.internal_balance_update(account, id.0, amount.0, true);
+            self.internal_supply_update(id.0, amount.0, true);
+        });
+
+        let event = Erc1155Event::TransferBatch {
+            operator: caller_address(),
+            from: account,
+            to: Address::from([0; 20]),
+            ids,
+            values: amounts,
+        };
+        // Emit the Tokens burned event
+        emit_event_experimental(event.clone());
+
+        l1x_sdk::msg(&serde_json::to_string(&event).unwrap_or_default());
+    }
+
     fn set_approval_for_all(&mut self, operator: Address, approved: bool) {
         // Get the caller Address
         let caller_id = l1x_sdk::caller_address();
@@ -334,35 +916,21 @@ impl NftContract {
         Self::assert_zero_address(&to);
         self.assert_caller_is_approved(&from);
 
-        if !self.balance_of.contains_key(&to) {
-            let new_map = BTreeMap::new();
-            self.balance_of.insert(to, new_map);
-        }
-        let mut cloned_tokens_to = self
-            .balance_of
-            .get(&to)
-            .cloned()
-            .expect("Can't get just created map");
+        self.internal_batch_transfer(from, to, ids, values);
+    }
 
-        let tokens_from = self.balance_of.get_mut(&from).expect("No enough fund");
+    fn internal_batch_transfer(
+        &mut self,
+        from: Address,
+        to: Address,
+        ids: Vec<U128>,
+        values: Vec<U128>,
+    ) {
         ids.iter().zip(values.iter()).for_each(|(id, amount)| {
-            let id: u128 = (*id).into();
-            let amount: u128 = (*amount).into();
-
-            let balance_from = tokens_from
-                .get_mut(&id)
-                .expect(&format!("No enough fund: Id {}", id));
-            *balance_from = balance_from
-                .checked_sub(amount)
-                .expect(&format!("No enough fund: Id {}", id));
-
-            let mut balance_to = *cloned_tokens_to.get(&id).unwrap_or(&0u128);
-            balance_to = balance_to.checked_add(amount).expect("Integer overflow");
-            cloned_tokens_to.insert(id, balance_to);
+            self.internal_balance_update(from, id.0, amount.0, true);
+            self.internal_balance_update(to, id.0, amount.0, false);
         });
 
-        self.balance_of.insert(to, cloned_tokens_to);
-
         let event = Erc1155Event::TransferBatch {
             operator: l1x_sdk::caller_address(),
             from,
@@ -380,12 +948,10 @@ impl NftContract {
     }
 
     fn balance_of(&self, owner: Address, id: u128) -> u128 {
-        *self
-            .balance_of
-            .get(&owner)
-            .unwrap_or(&BTreeMap::<u128, u128>::new())
-            .get(&id)
-            .unwrap_or(&0)
+        self.balance_of
+            .get(&(owner, id))
+            .map(|balance| balance.amount)
+            .unwrap_or_default()
     }
 
     fn balance_of_batch(&self, owners: Vec<Address>, ids: Vec<U128>) -> Vec<U128> {
@@ -402,15 +968,21 @@ impl NftContract {
             .collect()
     }
 
-    fn owned_tokens(&self, owner: Address) -> Vec<(U128, U128)> {
-        let issued_tokens = self
-            .balance_of
-            .get(&owner)
-            .unwrap_or_else(|| panic!("Not enough funds"));
+    fn owned_tokens(&self, owner: Address, from: u32, limit: Option<u32>) -> Vec<(U128, U128)> {
+        let Some(owned_ids) = self.owned_ids.get(&owner) else {
+            return Vec::new();
+        };
 
-        issued_tokens
-            .iter()
-            .map(|(k, v)| (U128::from(*k), U128::from(*v)))
+        let to = match limit {
+            Some(limit) => from.saturating_add(limit).min(owned_ids.len()),
+            None => owned_ids.len(),
+        };
+
+        (from..to)
+            .map(|idx| {
+                let id = *owned_ids.get(idx).expect("Can't get the owned token id");
+                (U128::from(id), U128::from(self.balance_of(owner, id)))
+            })
             .collect()
     }
 
@@ -422,7 +994,71 @@ impl NftContract {
             .unwrap_or(false)
     }
 
+    fn total_supply(&self, id: u128) -> u128 {
+        self.total_supply.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Calls the acceptance hook of a registered `to`. The hooks are called once the state is saved, so the
+    /// recipient sees its new balance and a reentrant call is not overwritten. A rejection reverts the whole call
+    fn notify_received(&self, from: Address, to: Address, id: U128, value: U128, data: Vec<u8>) {
+        if self.receivers.contains_key(&to) {
+            receiver::on_erc1155_received(caller_address(), from, to, id, value, data);
+        }
+    }
+
+    /// Calls the batch acceptance hook of a registered `to`, see `notify_received`
+    fn notify_batch_received(
+        &self,
+        from: Address,
+        to: Address,
+        ids: Vec<U128>,
+        values: Vec<U128>,
+        data: Vec<u8>,
+    ) {
+        if self.receivers.contains_key(&to) {
+            receiver::on_erc1155_batch_received(caller_address(), from, to, ids, values, data);
+        }
+    }
+
+    fn is_minter(&self, account: &Address) -> bool {
+        *account == contract_owner_address() || self.minters.contains_key(account)
+    }
+
+    fn migrate_balances(&mut self, holders: Vec<Address>) {
+        assert!(self.migrating_balances, "The balances are already migrated");
+        let mut legacy_balance_of: LookupMap<Address, BTreeMap<u128, u128>> =
+            LookupMap::new(STORAGE_LEGACY_BALANCE_OF_KEY.to_vec());
+
+        for holder in holders {
+            if let Some(balances) = legacy_balance_of.remove(holder) {
+                for (id, amount) in balances {
+                    self.internal_balance_update(holder, id, amount, false);
+                    self.internal_supply_update(id, amount, false);
+                }
+            }
+        }
+    }
+
+    fn state_version() -> u32 {
+        match l1x_sdk::storage_read(STORAGE_STATE_HEADER_KEY) {
+            Some(bytes) => StateHeader::try_from_slice(&bytes).unwrap().version,
+            None => LEGACY_STATE_VERSION,
+        }
+    }
+
+    fn save_state_version(version: u32) {
+        l1x_sdk::storage_write(
+            STORAGE_STATE_HEADER_KEY,
+            &StateHeader { version }.try_to_vec().unwrap(),
+        );
+    }
+
     fn load() -> Self {
+        assert_eq!(
+            Self::state_version(),
+            STATE_VERSION,
+            "The contract state is outdated, call `migrate` first"
+        );
         match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
             Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
             None => panic!("The contract isn't initialized"),
@@ -450,6 +1086,22 @@ impl NftContract {
         );
     }
 
+    fn assert_caller_is_minter(&self) {
+        let caller_id = caller_address();
+        assert!(
+            self.is_minter(&caller_id),
+            "Not Authorized, the caller is not a minter, CallerId: {}",
+            caller_id
+        );
+    }
+
+    fn assert_balances_migrated(&self) {
+        assert!(
+            !self.migrating_balances,
+            "The legacy balances are being migrated, call `nft_finish_balance_migration` first"
+        );
+    }
+
     fn assert_zero_address(address: &Address) {
         assert_ne!(
             *address,
@@ -0,0 +1,113 @@
+//! ERC-1155 acceptance hooks.
+//!
+//! The host can't tell whether an address is a contract instance without panicking for other addresses, so the
+//! hooks are called only for the recipients registered with `nft_set_receiver`. A registered recipient must
+//! accept the tokens, a failed call or another return value reverts the whole transaction
+use l1x_sdk::{
+    call_contract,
+    contract_interaction::ContractCall,
+    gas_left,
+    types::{Address, Gas, U128},
+};
+use serde::Serialize;
+
+/// The value `on_erc1155_received` must return to accept the tokens
+pub const ERC1155_RECEIVED: &str = "0xf23a6e61";
+
+/// The value `on_erc1155_batch_received` must return to accept the tokens
+pub const ERC1155_BATCH_RECEIVED: &str = "0xbc197c81";
+
+/// Gas kept by the token contract when it calls the recipient
+const RECEIVER_CALL_GAS_RESERVE: Gas = 10_000;
+
+/// Calls `on_erc1155_received` of `to`. Panics if the recipient rejects the tokens
+pub fn on_erc1155_received(
+    operator: Address,
+    from: Address,
+    to: Address,
+    id: U128,
+    value: U128,
+    data: Vec<u8>,
+) {
+    let args = {
+        #[derive(Serialize)]
+        struct Args {
+            operator: Address,
+            from: Address,
+            id: U128,
+            value: U128,
+            data: Vec<u8>,
+        }
+        serde_json::to_vec(&Args {
+            operator,
+            from,
+            id,
+            value,
+            data,
+        })
+        .unwrap()
+    };
+
+    call_receiver(to, "on_erc1155_received", args, ERC1155_RECEIVED);
+}
+
+/// Calls `on_erc1155_batch_received` of `to`. Panics if the recipient rejects the tokens
+pub fn on_erc1155_batch_received(
+    operator: Address,
+    from: Address,
+    to: Address,
+    ids: Vec<U128>,
+    values: Vec<U128>,
+    data: Vec<u8>,
+) {
+    let args = {
+        #[derive(Serialize)]
+        struct Args {
+            operator: Address,
+            from: Address,
+            ids: Vec<U128>,
+            values: Vec<U128>,
+            data: Vec<u8>,
+        }
+        serde_json::to_vec(&Args {
+            operator,
+            from,
+            ids,
+            values,
+            data,
+        })
+        .unwrap()
+    };
+
+    call_receiver(
+        to,
+        "on_erc1155_batch_received",
+        args,
+        ERC1155_BATCH_RECEIVED,
+    );
+}
+
+fn call_receiver(to: Address, method_name: &str, args: Vec<u8>, expected_ack: &str) {
+    let call = ContractCall {
+        contract_address: to,
+        method_name: method_name.to_string(),
+        args,
+        read_only: false,
+        gas_limit: gas_left().saturating_sub(RECEIVER_CALL_GAS_RESERVE),
+    };
+
+    match call_contract(&call) {
+        Ok(res) => {
+            let ack: Option<String> = serde_json::from_slice(&res).ok();
+            assert_eq!(
+                ack.as_deref(),
+                Some(expected_ack),
+                "Recipient {} rejected the tokens",
+                to
+            );
+        }
+        Err(e) => {
+            panic!("Recipient {} failed in {}: {}", to, method_name, e);
+        }
+    }
+}
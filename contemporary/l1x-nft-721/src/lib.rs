@@ -1,9 +1,14 @@
+mod sale;
+#[cfg(test)]
+mod test_host;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 use l1x_sdk::{
-    caller_address, contract, contract_owner_address, emit_event_experimental,
+    block_timestamp, caller_address, contract, contract_owner_address, emit_event_experimental,
     store::{LookupMap, Vector},
-    types::{Address, U128},
+    types::{Address, TimeStamp, U128},
 };
+use sale::{Sale, SaleConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -19,9 +24,34 @@ impl OwnerInfo {
     }
 }
 
+/// The address allowed to use a token without owning it, until `expires`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy)]
+struct UserInfo {
+    user: Address,
+    expires: TimeStamp,
+}
+
+impl UserInfo {
+    fn is_active(&self, now: TimeStamp) -> bool {
+        now < self.expires
+    }
+}
+
 /// Key for the storage of the contract data.
 const STORAGE_CONTRACT_KEY: &[u8] = b"state";
 
+/// Key for the storage of the state header.
+const STORAGE_STATE_HEADER_KEY: &[u8] = b"state-header";
+
+/// Layout of the contract data before the state header was introduced.
+const LEGACY_STATE_VERSION: u32 = 1;
+
+/// Layout of the contract data before rentals were introduced.
+const NO_RENTALS_STATE_VERSION: u32 = 2;
+
+/// Current layout of the contract data.
+const STATE_VERSION: u32 = 3;
+
 /// Key for the storage of the balance data.
 const STORAGE_BALANCE_OF_KEY: &[u8] = b"balances";
 
@@ -31,6 +61,12 @@ const STORAGE_BALANCE_IDS_KEY: &[u8] = b"ids";
 /// Key for the storage of the ownership data.
 const STORAGE_OWNER_OF_KEY: &[u8] = b"owners";
 
+/// Key for the storage of the token users (renters).
+const STORAGE_USER_OF_KEY: &[u8] = b"users";
+
+/// Key for the storage of burned token ids.
+const STORAGE_BURNED_NFTS_KEY: &[u8] = b"burned";
+
 /// Key for the storage of approved data.
 const STORAGE_GET_APPROVED_KEY: &[u8] = b"approved";
 
@@ -49,6 +85,17 @@ pub struct NFTMetadata {
     uri: String,
 }
 
+/// Defines whether a burned token id can be minted again
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq,
+)]
+pub enum BurnedIdPolicy {
+    /// Burned ids are retired forever
+    Forbid,
+    /// Burned ids can be minted again by `nft_mint_id_to`
+    Recycle,
+}
+
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
 enum NftEvent {
     NftTokenMinted(String),
@@ -56,10 +103,26 @@ enum NftEvent {
     NftTokenApproved(String),
     NftTokenApprovedForAll(String),
     NftTokenTransfered(String),
+    Transfer {
+        from: Address,
+        to: Address,
+        id: u128,
+    },
+    UpdateUser {
+        id: u128,
+        user: Address,
+        expires: TimeStamp,
+    },
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct NftContract {
+struct StateHeader {
+    version: u32,
+}
+
+/// Contract data of `LEGACY_STATE_VERSION`. Used only by `migrate`
+#[derive(BorshDeserialize)]
+struct NftContractV1 {
     metadata: NFTMetadata,
     current_token_id: u128,
     minted_total: u128,
@@ -70,6 +133,35 @@ pub struct NftContract {
     burned_nfts: BTreeSet<u128>,
 }
 
+/// Contract data of `NO_RENTALS_STATE_VERSION`. Used only by `migrate`
+#[derive(BorshDeserialize)]
+struct NftContractV2 {
+    metadata: NFTMetadata,
+    current_token_id: u128,
+    minted_total: u128,
+    balance_of: LookupMap<Address, Vector<u128>>,
+    owner_of: LookupMap<u128, OwnerInfo>,
+    get_approved: LookupMap<u128, Address>,
+    is_approved_for_all: LookupMap<Address, BTreeMap<Address, bool>>,
+    burned_nfts: LookupMap<u128, Address>,
+    burned_id_policy: BurnedIdPolicy,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct NftContract {
+    metadata: NFTMetadata,
+    current_token_id: u128,
+    minted_total: u128,
+    balance_of: LookupMap<Address, Vector<u128>>,
+    owner_of: LookupMap<u128, OwnerInfo>,
+    get_approved: LookupMap<u128, Address>,
+    is_approved_for_all: LookupMap<Address, BTreeMap<Address, bool>>,
+    /// Burned token id -> the last owner of the token
+    burned_nfts: LookupMap<u128, Address>,
+    burned_id_policy: BurnedIdPolicy,
+    user_of: LookupMap<u128, UserInfo>,
+}
+
 #[contract]
 impl NftContract {
     pub fn new(metadata: NFTMetadata) {
@@ -91,9 +183,44 @@ impl NftContract {
             owner_of: LookupMap::new(STORAGE_OWNER_OF_KEY.to_vec()),
             get_approved: LookupMap::new(STORAGE_GET_APPROVED_KEY.to_vec()),
             is_approved_for_all: LookupMap::new(STORAGE_IS_APPROVED_FOR_ALL_KEY.to_vec()),
-            burned_nfts: BTreeSet::new(),
+            burned_nfts: LookupMap::new(STORAGE_BURNED_NFTS_KEY.to_vec()),
+            burned_id_policy: BurnedIdPolicy::Forbid,
+            user_of: LookupMap::new(STORAGE_USER_OF_KEY.to_vec()),
+        };
+        contract.save();
+        Self::save_state_version(STATE_VERSION);
+    }
+
+    /// Converts the contract data of an older layout in place
+    pub fn migrate() {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+        let version = Self::state_version();
+        let bytes = l1x_sdk::storage_read(STORAGE_CONTRACT_KEY)
+            .unwrap_or_else(|| panic!("The contract isn't initialized"));
+
+        let mut contract = match version {
+            LEGACY_STATE_VERSION => Self::from_v1(NftContractV1::try_from_slice(&bytes).unwrap()),
+            NO_RENTALS_STATE_VERSION => {
+                Self::from_v2(NftContractV2::try_from_slice(&bytes).unwrap())
+            }
+            _ => panic!("The contract state is already migrated"),
         };
+
         contract.save();
+        Self::save_state_version(STATE_VERSION);
+
+        l1x_sdk::msg(&format!(
+            "Migrated the contract state from version {} to {}",
+            version, STATE_VERSION
+        ));
+    }
+
+    pub fn nft_state_version() -> u32 {
+        Self::state_version()
     }
 
     pub fn nft_name() -> String {
@@ -157,6 +284,85 @@ impl NftContract {
         new_token_id.into()
     }
 
+    pub fn nft_set_sale_config(config: SaleConfig) {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+        let mut sale = Sale::load();
+
+        sale.set_config(config);
+
+        sale.save();
+    }
+
+    pub fn nft_sale_config() -> Option<SaleConfig> {
+        let sale = Sale::load();
+        sale.config()
+    }
+
+    pub fn nft_public_mint(quantity: u32, proof: Vec<String>) -> Vec<U128> {
+        // load the contract storage state
+        let mut contract = Self::load();
+        let mut sale = Sale::load();
+
+        let buyer = caller_address();
+        let cost = sale.purchase(buyer, quantity, &proof, block_timestamp());
+        assert!(
+            l1x_sdk::address_balance(&buyer) >= cost,
+            "Not enough funds to pay {} for {} tokens",
+            cost,
+            quantity
+        );
+        if cost != 0 {
+            l1x_sdk::transfer_from_caller(cost);
+        }
+
+        let minted_ids = (0..quantity)
+            .map(|_| contract.mint_to(buyer).into())
+            .collect();
+
+        // Save the contract state
+        sale.save();
+        contract.save();
+
+        l1x_sdk::msg(&format!(
+            "Sold {} tokens to {} for {}",
+            quantity, buyer, cost
+        ));
+
+        minted_ids
+    }
+
+    pub fn nft_withdraw_proceeds(to: Address) -> U128 {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+        let mut sale = Sale::load();
+
+        let amount = sale.take_proceeds();
+        sale.save();
+
+        l1x_sdk::transfer_to(&to, amount);
+
+        l1x_sdk::msg(&format!("Withdrawn sale proceeds {} to {}", amount, to));
+
+        amount.into()
+    }
+
+    pub fn nft_sale_proceeds() -> U128 {
+        let sale = Sale::load();
+        sale.proceeds().into()
+    }
+
+    pub fn nft_minted_by(wallet: Address) -> u32 {
+        let sale = Sale::load();
+        sale.minted_by(&wallet)
+    }
+
     pub fn nft_burn(id: U128) {
         // load the contract storage state
         let mut contract = Self::load();
@@ -168,6 +374,60 @@ impl NftContract {
         contract.save();
     }
 
+    pub fn nft_set_burned_id_policy(policy: BurnedIdPolicy) {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        contract.burned_id_policy = policy;
+
+        // Save the contract state
+        contract.save();
+    }
+
+    pub fn nft_burned_id_policy() -> BurnedIdPolicy {
+        let contract = Self::load();
+        contract.burned_id_policy
+    }
+
+    pub fn nft_is_burned(id: U128) -> bool {
+        let contract = Self::load();
+        contract.burned_nfts.contains_key(&id.0)
+    }
+
+    pub fn nft_set_user(id: U128, user: Address, expires: U128) {
+        // load the contract storage state
+        let mut contract = Self::load();
+
+        // Call the internal implementation
+        contract.set_user(id.into(), user, expires.into());
+
+        // Save the contract state
+        contract.save();
+    }
+
+    pub fn nft_user_of(id: U128) -> Option<Address> {
+        // load the contract storage state
+        let contract = Self::load();
+
+        // Call the internal implementation
+        contract.user_of(id.into())
+    }
+
+    pub fn nft_user_expires(id: U128) -> U128 {
+        let contract = Self::load();
+        contract
+            .user_of
+            .get(&id.0)
+            .map(|info| info.expires)
+            .unwrap_or_default()
+            .into()
+    }
+
     pub fn nft_approve(spender: Address, id: U128) {
         // load the contract storage state
         let mut contract = Self::load();
@@ -264,6 +524,15 @@ impl NftContract {
         self.owner_of.remove(id);
         self.get_approved.remove(id);
 
+        // The user (renter) is not carried over to the next owner
+        if self.user_of.remove(id).is_some() {
+            emit_event_experimental(NftEvent::UpdateUser {
+                id,
+                user: Address::from([0u8; 20]),
+                expires: 0,
+            });
+        }
+
         (owner_info.address, balance_from.len())
     }
 
@@ -285,13 +554,35 @@ impl NftContract {
         self.owner_of.insert(id, OwnerInfo::new(to, last_idx));
     }
 
+    fn internal_is_owner_or_approved(
+        &self,
+        caller_id: &Address,
+        owner: &Address,
+        id: u128,
+    ) -> bool {
+        let caller_is_owner = owner == caller_id;
+        let is_approved_operator = self
+            .is_approved_for_all
+            .get(owner)
+            .and_then(|approved_map| approved_map.get(caller_id))
+            .copied()
+            .unwrap_or(false);
+        let is_approved_spender = self.get_approved.get(&id) == Some(caller_id);
+
+        caller_is_owner || is_approved_operator || is_approved_spender
+    }
+
     fn mint_id_to(&mut self, to: Address, id: u128) -> u128 {
         let new_token_id = id;
-        assert!(
-            !self.burned_nfts.contains(&id),
-            "Burned Token ID {:?} cannot be minted again",
-            new_token_id
-        );
+        if self.burned_nfts.contains_key(&id) {
+            assert_eq!(
+                self.burned_id_policy,
+                BurnedIdPolicy::Recycle,
+                "Burned Token ID {:?} cannot be minted again",
+                new_token_id
+            );
+            self.burned_nfts.remove(id);
+        }
         assert!(
             !self.owner_of.contains_key(&new_token_id),
             "Token ID {:?} already exist",
@@ -322,7 +613,9 @@ impl NftContract {
 
         // Find the closed available id
         while new_token_id <= L1X_NFT_TOTAL_SUPPLY {
-            if !self.owner_of.contains_key(&new_token_id) {
+            let is_retired = self.burned_id_policy == BurnedIdPolicy::Forbid
+                && self.burned_nfts.contains_key(&new_token_id);
+            if !self.owner_of.contains_key(&new_token_id) && !is_retired {
                 break;
             }
             new_token_id += 1;
@@ -338,27 +631,37 @@ impl NftContract {
     }
 
     fn burn(&mut self, id: u128) {
-        assert_eq!(
-            caller_address(),
-            contract_owner_address(),
-            "Only the contract owner can call this method"
-        );
+        let caller_id = caller_address();
+
+        let owner_info = self
+            .owner_of
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| panic!("Token ID {:#?} Not Minted or Doesn't exist", id));
 
         assert!(
-            self.owner_of.get(&id).is_some(),
-            "Token ID {:#?} Not Minted or Doesn't exist",
+            self.internal_is_owner_or_approved(&caller_id, &owner_info.address, id),
+            "Not Authorized, the caller, neither an owner, nor an approved spender, nor an approved operator,
+             CallerId: {}, Token Owner: {}, TokenID: {}",
+            caller_id,
+            owner_info.address,
             id
         );
 
         let (from, balance_from) = self.internal_remove_token(id);
 
         // update id to burned_nfts storage
-        self.burned_nfts.insert(id);
-        // Emit the Token burned event
+        self.burned_nfts.insert(id, from);
+        // Emit the Token burned events
         emit_event_experimental(NftEvent::NftTokenBurned(format!(
             "Burn Token_ID {:#?} from Owner {} Balance {:#?}",
             id, from, balance_from
         )));
+        emit_event_experimental(NftEvent::Transfer {
+            from,
+            to: Address::from([0u8; 20]),
+            id,
+        });
 
         l1x_sdk::msg(&format!(
             "Burn Token_ID {:#?} from Owner {} Balance {:#?}",
@@ -366,6 +669,41 @@ impl NftContract {
         ));
     }
 
+    fn set_user(&mut self, id: u128, user: Address, expires: TimeStamp) {
+        let caller_id = caller_address();
+
+        let owner_info = self
+            .owner_of
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| panic!("Token ID {:#?} Not Minted or Doesn't exist", id));
+
+        assert!(
+            self.internal_is_owner_or_approved(&caller_id, &owner_info.address, id),
+            "Not Authorized, the caller, neither an owner, nor an approved spender, nor an approved operator,
+             CallerId: {}, Token Owner: {}, TokenID: {}",
+            caller_id,
+            owner_info.address,
+            id
+        );
+
+        self.user_of.insert(id, UserInfo { user, expires });
+
+        emit_event_experimental(NftEvent::UpdateUser { id, user, expires });
+
+        l1x_sdk::msg(&format!(
+            "User {} is set for Token_id {:#?} until {}",
+            user, id, expires
+        ));
+    }
+
+    fn user_of(&self, id: u128) -> Option<Address> {
+        self.user_of
+            .get(&id)
+            .filter(|info| info.is_active(block_timestamp()))
+            .map(|info| info.user)
+    }
+
     fn approve(&mut self, spender: Address, id: u128) {
         // Get the caller Address
         let caller_id = l1x_sdk::caller_address();
@@ -459,20 +797,7 @@ impl NftContract {
             from, &owner_info.address
         );
 
-        let caller_is_owner = owner_info.address == caller_id;
-        let is_approved_operator = {
-            self.is_approved_for_all
-                .get(&from)
-                .and_then(|approved_map| approved_map.get(&caller_id))
-                .copied()
-                .unwrap_or(false)
-        };
-        let is_approved_spender = {
-            let spender_id = self.get_approved.get(&id);
-            spender_id == Some(&caller_id)
-        };
-
-        assert!(caller_is_owner || is_approved_operator || is_approved_spender,
+        assert!(self.internal_is_owner_or_approved(&caller_id, &from, id),
             "Not Authorized, the caller, neither an owner, nor an approved spender, nor an approved operator,
              CallerId: {}, Token Owner: {}, From: {}, TokenID: {}", caller_id, owner_info.address, from, id);
 
@@ -526,7 +851,61 @@ impl NftContract {
         result
     }
 
+    fn from_v1(legacy: NftContractV1) -> Self {
+        let mut contract = Self {
+            metadata: legacy.metadata,
+            current_token_id: legacy.current_token_id,
+            minted_total: legacy.minted_total,
+            balance_of: legacy.balance_of,
+            owner_of: legacy.owner_of,
+            get_approved: legacy.get_approved,
+            is_approved_for_all: legacy.is_approved_for_all,
+            burned_nfts: LookupMap::new(STORAGE_BURNED_NFTS_KEY.to_vec()),
+            burned_id_policy: BurnedIdPolicy::Forbid,
+            user_of: LookupMap::new(STORAGE_USER_OF_KEY.to_vec()),
+        };
+        // The last owner wasn't tracked before, the zero address is used instead
+        for id in legacy.burned_nfts {
+            contract.burned_nfts.insert(id, Address::from([0u8; 20]));
+        }
+        contract
+    }
+
+    fn from_v2(legacy: NftContractV2) -> Self {
+        Self {
+            metadata: legacy.metadata,
+            current_token_id: legacy.current_token_id,
+            minted_total: legacy.minted_total,
+            balance_of: legacy.balance_of,
+            owner_of: legacy.owner_of,
+            get_approved: legacy.get_approved,
+            is_approved_for_all: legacy.is_approved_for_all,
+            burned_nfts: legacy.burned_nfts,
+            burned_id_policy: legacy.burned_id_policy,
+            user_of: LookupMap::new(STORAGE_USER_OF_KEY.to_vec()),
+        }
+    }
+
+    fn state_version() -> u32 {
+        match l1x_sdk::storage_read(STORAGE_STATE_HEADER_KEY) {
+            Some(bytes) => StateHeader::try_from_slice(&bytes).unwrap().version,
+            None => LEGACY_STATE_VERSION,
+        }
+    }
+
+    fn save_state_version(version: u32) {
+        l1x_sdk::storage_write(
+            STORAGE_STATE_HEADER_KEY,
+            &StateHeader { version }.try_to_vec().unwrap(),
+        );
+    }
+
     fn load() -> Self {
+        assert_eq!(
+            Self::state_version(),
+            STATE_VERSION,
+            "The contract state is outdated, call `migrate` first"
+        );
         match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
             Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
             None => panic!("The contract isn't initialized"),
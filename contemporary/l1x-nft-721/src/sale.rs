@@ -0,0 +1,401 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_sdk::{
+    store::LookupMap,
+    types::{Address, TimeStamp, U128},
+};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+/// Key for the storage of the primary sale data.
+const STORAGE_SALE_KEY: &[u8] = b"sale";
+
+/// Key for the storage of the number of tokens minted by a wallet during the sale.
+const STORAGE_MINTED_PER_WALLET_KEY: &[u8] = b"sale-minted";
+
+type Hash = [u8; 32];
+
+/// Fixed-price public sale configuration.
+///
+/// `start_time` and `end_time` use the same units as `block_timestamp()`. The sale is open
+/// while `start_time <= block_timestamp() < end_time`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+pub struct SaleConfig {
+    /// Price of one token in native L1X coins
+    pub price: U128,
+    /// How many tokens a single wallet can mint
+    pub max_per_wallet: u32,
+    pub start_time: U128,
+    pub end_time: U128,
+    /// Hex encoded keccak256 Merkle root of the allowed wallets. `None` opens the sale to everyone
+    pub allowlist_root: Option<String>,
+}
+
+impl SaleConfig {
+    fn validate(&self) {
+        assert!(
+            self.start_time.0 < self.end_time.0,
+            "The sale start time must be before the end time"
+        );
+        assert!(
+            self.max_per_wallet > 0,
+            "The per-wallet limit should be greater than 0"
+        );
+        if let Some(root) = &self.allowlist_root {
+            decode_hash(root);
+        }
+    }
+
+    fn is_open(&self, now: TimeStamp) -> bool {
+        self.start_time.0 <= now && now < self.end_time.0
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Sale {
+    config: Option<SaleConfig>,
+    /// Coins collected by the sale and not withdrawn yet
+    proceeds: u128,
+    minted_per_wallet: LookupMap<Address, u32>,
+}
+
+impl Sale {
+    pub fn config(&self) -> Option<SaleConfig> {
+        self.config.clone()
+    }
+
+    pub fn set_config(&mut self, config: SaleConfig) {
+        config.validate();
+        self.config = Some(config);
+    }
+
+    pub fn proceeds(&self) -> u128 {
+        self.proceeds
+    }
+
+    pub fn minted_by(&self, wallet: &Address) -> u32 {
+        self.minted_per_wallet
+            .get(wallet)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Checks that `buyer` can mint `quantity` tokens now, records the purchase and returns the price to collect
+    pub fn purchase(
+        &mut self,
+        buyer: Address,
+        quantity: u32,
+        proof: &[String],
+        now: TimeStamp,
+    ) -> u128 {
+        assert!(quantity > 0, "Quantity should be greater than 0");
+
+        let config = self.config.as_ref().expect("The sale is not configured");
+        assert!(config.is_open(now), "The sale is not active");
+
+        if let Some(root) = &config.allowlist_root {
+            let proof = proof
+                .iter()
+                .map(|node| decode_hash(node))
+                .collect::<Vec<_>>();
+            assert!(
+                verify_merkle_proof(&decode_hash(root), keccak256(buyer.as_bytes()), &proof),
+                "{} is not in the allowlist",
+                buyer
+            );
+        }
+
+        let minted = self
+            .minted_by(&buyer)
+            .checked_add(quantity)
+            .expect("Minted quantity overflowed");
+        assert!(
+            minted <= config.max_per_wallet,
+            "Wallet {} can't mint more than {} tokens",
+            buyer,
+            config.max_per_wallet
+        );
+
+        let cost = config
+            .price
+            .0
+            .checked_mul(quantity.into())
+            .expect("Sale cost overflowed");
+
+        self.minted_per_wallet.insert(buyer, minted);
+        self.proceeds = self
+            .proceeds
+            .checked_add(cost)
+            .expect("Sale proceeds overflowed");
+
+        cost
+    }
+
+    /// Resets the collected proceeds and returns the amount to pay out
+    pub fn take_proceeds(&mut self) -> u128 {
+        assert_ne!(self.proceeds, 0, "There are no proceeds to withdraw");
+        std::mem::take(&mut self.proceeds)
+    }
+
+    pub fn load() -> Self {
+        match l1x_sdk::storage_read(STORAGE_SALE_KEY) {
+            Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
+            None => Self {
+                config: None,
+                proceeds: 0,
+                minted_per_wallet: LookupMap::new(STORAGE_MINTED_PER_WALLET_KEY.to_vec()),
+            },
+        }
+    }
+
+    pub fn save(&mut self) {
+        l1x_sdk::storage_write(STORAGE_SALE_KEY, &self.try_to_vec().unwrap());
+    }
+}
+
+fn keccak256(data: &[u8]) -> Hash {
+    let mut hasher = Keccak::v256();
+    let mut output = Hash::default();
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+fn decode_hash(value: &str) -> Hash {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .unwrap_or_else(|e| panic!("Invalid hex value {}: {}", value, e));
+    bytes
+        .try_into()
+        .unwrap_or_else(|_| panic!("{} is not a 32-byte hash", value))
+}
+
+/// Verifies a proof built with sorted pairs, compatible with OpenZeppelin `MerkleProof`
+fn verify_merkle_proof(root: &Hash, leaf: Hash, proof: &[Hash]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak256(&[node, *sibling].concat())
+        } else {
+            keccak256(&[*sibling, node].concat())
+        }
+    });
+
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_host::{self, set_balance, set_block_timestamp, set_caller};
+    use crate::{NFTMetadata, NftContract};
+
+    const PRICE: u128 = 100;
+    const START_TIME: u128 = 10;
+    const END_TIME: u128 = 20;
+
+    fn leaf(byte: u8) -> Hash {
+        keccak256(&[byte; 20])
+    }
+
+    fn hash_pair(a: Hash, b: Hash) -> Hash {
+        if a <= b {
+            keccak256(&[a, b].concat())
+        } else {
+            keccak256(&[b, a].concat())
+        }
+    }
+
+    /// The tree of 4 leaves and the proofs of its leaves
+    fn tree() -> (Hash, Vec<(Hash, Vec<Hash>)>) {
+        let leaves = [leaf(1), leaf(2), leaf(3), leaf(4)];
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        let proofs = vec![
+            (leaves[0], vec![leaves[1], right]),
+            (leaves[1], vec![leaves[0], right]),
+            (leaves[2], vec![leaves[3], left]),
+            (leaves[3], vec![leaves[2], left]),
+        ];
+        (root, proofs)
+    }
+
+    #[test]
+    fn empty_proof_matches_only_the_root() {
+        assert!(verify_merkle_proof(&leaf(1), leaf(1), &[]));
+        assert!(!verify_merkle_proof(&leaf(1), leaf(2), &[]));
+    }
+
+    #[test]
+    fn valid_proofs() {
+        let (root, proofs) = tree();
+        for (leaf, proof) in proofs {
+            assert!(verify_merkle_proof(&root, leaf, &proof));
+        }
+    }
+
+    #[test]
+    fn pairs_are_sorted() {
+        let (a, b) = (leaf(1), leaf(2));
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+
+        // The root of a tree built with sorted pairs doesn't depend on the leaf order
+        let root = keccak256(&[low, high].concat());
+        assert!(verify_merkle_proof(&root, a, &[b]));
+        assert!(verify_merkle_proof(&root, b, &[a]));
+
+        // A tree built without sorting the pairs is not accepted
+        let unsorted_root = keccak256(&[high, low].concat());
+        assert!(!verify_merkle_proof(&unsorted_root, low, &[high]));
+        assert!(!verify_merkle_proof(&unsorted_root, high, &[low]));
+    }
+
+    #[test]
+    fn wrong_leaf_or_proof() {
+        let (root, proofs) = tree();
+        let (_, proof) = &proofs[0];
+
+        assert!(!verify_merkle_proof(&root, leaf(5), proof));
+        assert!(!verify_merkle_proof(&root, proofs[2].0, proof));
+        assert!(!verify_merkle_proof(&root, proofs[0].0, &proof[..1]));
+        assert!(!verify_merkle_proof(
+            &keccak256(b"root"),
+            proofs[0].0,
+            proof
+        ));
+    }
+
+    fn wallet(byte: u8) -> Address {
+        Address::from([byte; 20])
+    }
+
+    fn config(allowlist_root: Option<String>) -> SaleConfig {
+        SaleConfig {
+            price: PRICE.into(),
+            max_per_wallet: 3,
+            start_time: START_TIME.into(),
+            end_time: END_TIME.into(),
+            allowlist_root,
+        }
+    }
+
+    fn sale(config: SaleConfig) -> Sale {
+        let mut sale = Sale::load();
+        sale.set_config(config);
+        sale
+    }
+
+    #[test]
+    fn purchase_returns_the_cost() {
+        let mut sale = sale(config(None));
+
+        assert_eq!(sale.purchase(wallet(1), 2, &[], START_TIME), 2 * PRICE);
+        assert_eq!(sale.purchase(wallet(1), 1, &[], END_TIME - 1), PRICE);
+        assert_eq!(sale.minted_by(&wallet(1)), 3);
+        assert_eq!(sale.proceeds(), 3 * PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "The sale is not active")]
+    fn purchase_before_the_start() {
+        sale(config(None)).purchase(wallet(1), 1, &[], START_TIME - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The sale is not active")]
+    fn purchase_at_the_end() {
+        sale(config(None)).purchase(wallet(1), 1, &[], END_TIME);
+    }
+
+    #[test]
+    #[should_panic(expected = "The sale is not configured")]
+    fn purchase_without_config() {
+        Sale::load().purchase(wallet(1), 1, &[], START_TIME);
+    }
+
+    #[test]
+    fn wallet_limit_is_per_wallet() {
+        let mut sale = sale(config(None));
+
+        sale.purchase(wallet(1), 3, &[], START_TIME);
+        sale.purchase(wallet(2), 3, &[], START_TIME);
+        assert_eq!(sale.minted_by(&wallet(1)), 3);
+        assert_eq!(sale.minted_by(&wallet(2)), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't mint more than 3 tokens")]
+    fn purchase_above_the_wallet_limit() {
+        let mut sale = sale(config(None));
+
+        sale.purchase(wallet(1), 2, &[], START_TIME);
+        sale.purchase(wallet(1), 2, &[], START_TIME);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sale cost overflowed")]
+    fn purchase_cost_overflow() {
+        let mut sale = sale(SaleConfig {
+            price: u128::MAX.into(),
+            ..config(None)
+        });
+
+        sale.purchase(wallet(1), 2, &[], START_TIME);
+    }
+
+    #[test]
+    fn purchase_with_allowlist() {
+        let root = hex::encode(hash_pair(leaf(1), leaf(2)));
+        let mut sale = sale(config(Some(root)));
+
+        let proof = [hex::encode(leaf(2))];
+        assert_eq!(sale.purchase(wallet(1), 1, &proof, START_TIME), PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not in the allowlist")]
+    fn purchase_outside_the_allowlist() {
+        let root = hex::encode(hash_pair(leaf(1), leaf(2)));
+        let mut sale = sale(config(Some(root)));
+
+        let proof = [hex::encode(leaf(2))];
+        sale.purchase(wallet(3), 1, &proof, START_TIME);
+    }
+
+    /// Initializes the contract and opens the sale without an allowlist
+    fn setup_public_mint() {
+        set_caller(test_host::owner());
+        NftContract::new(NFTMetadata {
+            name: "Sale".to_string(),
+            decimals: 0,
+            symbol: "SALE".to_string(),
+            icon: None,
+            uri: "https://example.com/".to_string(),
+        });
+        NftContract::nft_set_sale_config(config(None));
+        set_block_timestamp(START_TIME);
+    }
+
+    #[test]
+    fn public_mint_collects_the_cost() {
+        setup_public_mint();
+        set_balance(wallet(1), 2 * PRICE + 50);
+
+        set_caller(wallet(1));
+        let minted = NftContract::nft_public_mint(2, Vec::new());
+
+        assert_eq!(minted.len(), 2);
+        assert_eq!(test_host::balance(wallet(1)), 50);
+        assert_eq!(test_host::balance(test_host::instance()), 2 * PRICE);
+        assert_eq!(NftContract::nft_sale_proceeds().0, 2 * PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough funds")]
+    fn public_mint_underpayment() {
+        setup_public_mint();
+        set_balance(wallet(1), 2 * PRICE - 1);
+
+        set_caller(wallet(1));
+        NftContract::nft_public_mint(2, Vec::new());
+    }
+}
@@ -0,0 +1,198 @@
+//! In-memory implementation of the host functions used by the contract, so the contract can be called from
+//! native unit tests. The state is kept per test thread
+use std::{cell::RefCell, collections::HashMap};
+
+use l1x_sdk::types::Address;
+
+/// The contract owner address
+pub fn owner() -> Address {
+    Address::from([0xff; 20])
+}
+
+/// The contract instance address
+pub fn instance() -> Address {
+    Address::from([0xee; 20])
+}
+
+#[derive(Default)]
+struct Host {
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+    registers: HashMap<u64, Vec<u8>>,
+    caller: Option<Address>,
+    block_timestamp: u128,
+    balances: HashMap<Address, u128>,
+}
+
+thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::default());
+}
+
+pub fn set_caller(caller: Address) {
+    HOST.with(|host| host.borrow_mut().caller = Some(caller));
+}
+
+pub fn set_block_timestamp(block_timestamp: u128) {
+    HOST.with(|host| host.borrow_mut().block_timestamp = block_timestamp);
+}
+
+pub fn set_balance(address: Address, balance: u128) {
+    HOST.with(|host| host.borrow_mut().balances.insert(address, balance));
+}
+
+pub fn balance(address: Address) -> u128 {
+    HOST.with(|host| {
+        host.borrow()
+            .balances
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+    })
+}
+
+/// Moves `amount` between the balances, returns 1 on success and 0 if `from` doesn't have enough coins
+fn transfer(from: Address, to: Address, amount: u128) -> u64 {
+    if balance(from) < amount {
+        return 0;
+    }
+    set_balance(from, balance(from) - amount);
+    set_balance(to, balance(to) + amount);
+    1
+}
+
+fn read_amount(amount_addr: u64, amount_len: u64) -> u128 {
+    u128::from_le_bytes(read_memory(amount_addr, amount_len).try_into().unwrap())
+}
+
+fn read_memory(addr: u64, len: u64) -> Vec<u8> {
+    unsafe { std::slice::from_raw_parts(addr as *const u8, len as usize) }.to_vec()
+}
+
+fn write_memory(addr: u64, data: &[u8]) {
+    unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), addr as *mut u8, data.len()) }
+}
+
+fn set_register(register_id: u64, data: Vec<u8>) {
+    HOST.with(|host| host.borrow_mut().registers.insert(register_id, data));
+}
+
+/// Moves `value` to the register, returns 1 if there is a value and 0 otherwise
+fn return_in_register(register_id: u64, value: Option<Vec<u8>>) -> u64 {
+    match value {
+        Some(value) => {
+            set_register(register_id, value);
+            1
+        }
+        None => 0,
+    }
+}
+
+#[no_mangle]
+extern "C" fn read_register(register_id: u64, result_addr: u64) {
+    HOST.with(|host| write_memory(result_addr, &host.borrow().registers[&register_id]));
+}
+
+#[no_mangle]
+extern "C" fn register_len(register_id: u64) -> u64 {
+    HOST.with(|host| {
+        host.borrow()
+            .registers
+            .get(&register_id)
+            .map_or(u64::MAX, |data| data.len() as u64)
+    })
+}
+
+#[no_mangle]
+extern "C" fn storage_write(
+    key_addr: u64,
+    key_len: u64,
+    value_addr: u64,
+    value_len: u64,
+    evicted_register_id: u64,
+) -> u64 {
+    let key = read_memory(key_addr, key_len);
+    let value = read_memory(value_addr, value_len);
+    let evicted = HOST.with(|host| host.borrow_mut().storage.insert(key, value));
+    return_in_register(evicted_register_id, evicted)
+}
+
+#[no_mangle]
+extern "C" fn storage_read(key_addr: u64, key_len: u64, register_id: u64) -> u64 {
+    let key = read_memory(key_addr, key_len);
+    let value = HOST.with(|host| host.borrow().storage.get(&key).cloned());
+    return_in_register(register_id, value)
+}
+
+#[no_mangle]
+extern "C" fn storage_remove(key_addr: u64, key_len: u64, register_id: u64) -> u64 {
+    let key = read_memory(key_addr, key_len);
+    let removed = HOST.with(|host| host.borrow_mut().storage.remove(&key));
+    return_in_register(register_id, removed)
+}
+
+#[no_mangle]
+extern "C" fn contract_owner_address(register_id: u64) {
+    set_register(register_id, owner().to_vec());
+}
+
+#[no_mangle]
+extern "C" fn contract_instance_address(register_id: u64) {
+    set_register(register_id, instance().to_vec());
+}
+
+#[no_mangle]
+extern "C" fn caller_address(register_id: u64) {
+    let caller = HOST.with(|host| host.borrow().caller.expect("The caller is not set"));
+    set_register(register_id, caller.to_vec());
+}
+
+#[no_mangle]
+extern "C" fn block_timestamp(output_addr: u64, output_len: u64) {
+    let block_timestamp = HOST.with(|host| host.borrow().block_timestamp);
+    assert_eq!(output_len, 16);
+    write_memory(output_addr, &block_timestamp.to_le_bytes());
+}
+
+#[no_mangle]
+extern "C" fn address_balance(address_addr: u64, address_len: u64, register_id: u64) {
+    let address = Address::try_from(read_memory(address_addr, address_len)).unwrap();
+    set_register(register_id, balance(address).to_le_bytes().to_vec());
+}
+
+#[no_mangle]
+extern "C" fn transfer_from_caller(amount_addr: u64, amount_len: u64) -> u64 {
+    let caller = HOST.with(|host| host.borrow().caller.expect("The caller is not set"));
+    transfer(caller, instance(), read_amount(amount_addr, amount_len))
+}
+
+#[no_mangle]
+extern "C" fn transfer_to(
+    address_addr: u64,
+    address_len: u64,
+    amount_addr: u64,
+    amount_len: u64,
+) -> u64 {
+    let to = Address::try_from(read_memory(address_addr, address_len)).unwrap();
+    transfer(instance(), to, read_amount(amount_addr, amount_len))
+}
+
+#[no_mangle]
+extern "C" fn msg(_addr: u64, _len: u64) {}
+
+#[no_mangle]
+extern "C" fn emit_event_experimental(_data_addr: u64, _len: u64) -> u64 {
+    1
+}
+
+#[no_mangle]
+extern "C" fn panic() -> ! {
+    std::process::abort()
+}
+
+#[no_mangle]
+extern "C" fn panic_msg(msg_addr: u64, msg_len: u64) -> ! {
+    eprintln!(
+        "{}",
+        String::from_utf8_lossy(&read_memory(msg_addr, msg_len))
+    );
+    std::process::abort()
+}
@@ -0,0 +1,206 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{Attribute, FnArg, Ident, ItemTrait, Pat, ReturnType, TraitItem, TraitItemMethod};
+
+/// The generated code refers to the runtime crate only, so the calling crate doesn't need `serde` or `borsh`
+fn runtime() -> TokenStream2 {
+    quote! { ::l1x_contract_client }
+}
+
+fn doc_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .collect()
+}
+
+/// Generates `try_<method>` returning `Result` and `<method>` panicking on errors
+fn client_method(method: &TraitItemMethod) -> syn::Result<TokenStream2> {
+    let runtime = runtime();
+    let sig = &method.sig;
+
+    if let Some(default) = &method.default {
+        return Err(syn::Error::new(
+            default.span(),
+            "Contract client methods can't have a default implementation",
+        ));
+    }
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() {
+        return Err(syn::Error::new(
+            sig.span(),
+            "Contract client methods can't be generic or async",
+        ));
+    }
+
+    let mut receiver = None;
+    let mut arg_names = Vec::new();
+    let mut arg_types = Vec::new();
+    for input in &sig.inputs {
+        match input {
+            FnArg::Receiver(r) if r.reference.is_some() => receiver = Some(r),
+            FnArg::Receiver(r) => {
+                return Err(syn::Error::new(
+                    r.span(),
+                    "Expected `&self` for read-only calls or `&mut self` for state changing calls",
+                ))
+            }
+            FnArg::Typed(typed) => match typed.pat.as_ref() {
+                Pat::Ident(pat) => {
+                    arg_names.push(pat.ident.clone());
+                    arg_types.push(typed.ty.as_ref().clone());
+                }
+                pat => {
+                    return Err(syn::Error::new(
+                        pat.span(),
+                        "Contract client arguments should be plain identifiers",
+                    ))
+                }
+            },
+        }
+    }
+    let receiver = receiver.ok_or_else(|| {
+        syn::Error::new(
+            sig.span(),
+            "Expected `&self` for read-only calls or `&mut self` for state changing calls",
+        )
+    })?;
+    let read_only = receiver.mutability.is_none();
+
+    let ident = &sig.ident;
+    let try_ident = format_ident!("try_{}", ident);
+    let method_name = ident.to_string();
+    let docs = doc_attrs(&method.attrs);
+
+    let (output, call) = match &sig.output {
+        ReturnType::Default => (quote! { () }, quote! { call_empty }),
+        ReturnType::Type(_, ty) => (quote! { #ty }, quote! { call }),
+    };
+
+    Ok(quote! {
+        #(#docs)*
+        pub fn #try_ident(#receiver, #(#arg_names: #arg_types),*) -> ::core::result::Result<#output, #runtime::CallError> {
+            #[derive(#runtime::serde::Serialize)]
+            #[serde(crate = "::l1x_contract_client::serde")]
+            struct Args {
+                #(#arg_names: #arg_types,)*
+            }
+            let args = #runtime::serialize_args(#method_name, &Args { #(#arg_names,)* })?;
+            #runtime::#call(&self.address, #method_name, args, #read_only, self.gas_limit())
+        }
+
+        #(#docs)*
+        ///
+        /// # Panics
+        ///
+        /// Panics if the call fails
+        pub fn #ident(#receiver, #(#arg_names: #arg_types),*) -> #output {
+            self.#try_ident(#(#arg_names),*)
+                .unwrap_or_else(|e| ::core::panic!("{}", e))
+        }
+    })
+}
+
+fn client(name: Ident, input: ItemTrait) -> syn::Result<TokenStream2> {
+    let runtime = runtime();
+    let vis = &input.vis;
+    let docs = doc_attrs(&input.attrs);
+
+    let mut methods = TokenStream2::new();
+    for item in &input.items {
+        match item {
+            TraitItem::Method(method) => methods.extend(client_method(method)?),
+            item => {
+                return Err(syn::Error::new(
+                    item.span(),
+                    "Only methods can be declared in a contract client",
+                ))
+            }
+        }
+    }
+
+    Ok(quote! {
+        #(#docs)*
+        #[derive(Clone, Debug)]
+        #vis struct #name {
+            address: #runtime::l1x_sdk::types::Address,
+            gas_limit: ::core::option::Option<#runtime::l1x_sdk::types::Gas>,
+        }
+
+        // A contract rarely calls every method of the client
+        #[allow(dead_code)]
+        impl #name {
+            pub fn new(address: #runtime::l1x_sdk::types::Address) -> Self {
+                Self {
+                    address,
+                    gas_limit: None,
+                }
+            }
+
+            /// Sets the gas limit of every call made by this client.
+            ///
+            /// Without it a call gets all the gas left except `l1x_contract_client::GAS_RESERVE`
+            pub fn with_gas_limit(mut self, gas_limit: #runtime::l1x_sdk::types::Gas) -> Self {
+                self.gas_limit = Some(gas_limit);
+                self
+            }
+
+            pub fn address(&self) -> #runtime::l1x_sdk::types::Address {
+                self.address
+            }
+
+            fn gas_limit(&self) -> #runtime::l1x_sdk::types::Gas {
+                self.gas_limit.unwrap_or_else(#runtime::default_gas_limit)
+            }
+
+            #methods
+        }
+
+        // Only the address is stored, so the clients kept in a contract state stay compatible with the hand
+        // written ones. A restored client uses the default gas limit
+        impl #runtime::borsh::BorshSerialize for #name {
+            fn serialize<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                #runtime::borsh::BorshSerialize::serialize(&self.address, writer)
+            }
+        }
+
+        impl #runtime::borsh::BorshDeserialize for #name {
+            fn deserialize(buf: &mut &[u8]) -> ::std::io::Result<Self> {
+                #runtime::borsh::BorshDeserialize::deserialize(buf).map(Self::new)
+            }
+        }
+    })
+}
+
+/// Generates a typed client for the contract methods declared by a trait.
+///
+/// The trait itself is not emitted, the attribute replaces it with the client struct named by the attribute
+/// argument. Methods taking `&self` are read-only calls, methods taking `&mut self` are state changing calls.
+/// Each method `foo` generates `try_foo` returning `Result<_, CallError>` and `foo` panicking on errors.
+/// Methods without a return type expect the called method to return nothing.
+///
+/// # Example
+/// ```ignore
+/// use l1x_contract_client::contract_client;
+/// use l1x_sdk::types::Address;
+///
+/// #[contract_client(NameStorageContract)]
+/// pub trait NameStorage {
+///     fn get_names(&self) -> Vec<String>;
+///     fn add_name(&mut self, name: String);
+/// }
+///
+/// let mut names = NameStorageContract::new(address).with_gas_limit(30_000);
+/// names.add_name("XTalk".to_string());
+/// let names: Result<Vec<String>, _> = names.try_get_names();
+/// ```
+#[proc_macro_attribute]
+pub fn contract_client(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let name = syn::parse_macro_input!(attr as Ident);
+    let input = syn::parse_macro_input!(item as ItemTrait);
+
+    client(name, input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,169 @@
+//! Typed clients for cross contract calls.
+//!
+//! See [`contract_client`] for generating a client from a trait.
+use std::fmt;
+
+use l1x_sdk::{
+    call_contract,
+    contract_interaction::ContractCall,
+    gas_left,
+    types::{Address, Gas},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+pub use l1x_contract_client_macros::contract_client;
+
+#[doc(hidden)]
+pub use borsh;
+#[doc(hidden)]
+pub use l1x_sdk;
+#[doc(hidden)]
+pub use serde;
+#[doc(hidden)]
+pub use serde_json;
+
+/// The gas kept by the caller when a client doesn't have a gas limit
+pub const GAS_RESERVE: Gas = 10_000;
+
+/// Why a cross contract call failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallError {
+    /// The arguments couldn't be serialized
+    InvalidArgs { method: String, message: String },
+    /// The called method failed or the host couldn't execute the call
+    CallFailed {
+        contract: Address,
+        method: String,
+        message: String,
+    },
+    /// The called method returned nothing but a value was expected
+    EmptyResult { contract: Address, method: String },
+    /// The called method returned a value but nothing was expected
+    UnexpectedResult { contract: Address, method: String },
+    /// The returned value couldn't be deserialized
+    InvalidResult {
+        contract: Address,
+        method: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidArgs { method, message } => {
+                write!(
+                    f,
+                    "Can't serialize the arguments of {}: {}",
+                    method, message
+                )
+            }
+            Self::CallFailed {
+                contract,
+                method,
+                message,
+            } => write!(
+                f,
+                "Got the error when called {} of contract {}: {}",
+                method, contract, message
+            ),
+            Self::EmptyResult { contract, method } => write!(
+                f,
+                "{} of contract {} returned the empty result",
+                method, contract
+            ),
+            Self::UnexpectedResult { contract, method } => write!(
+                f,
+                "{} of contract {} returned something but that was not expected",
+                method, contract
+            ),
+            Self::InvalidResult {
+                contract,
+                method,
+                message,
+            } => write!(
+                f,
+                "Can't deserialize the value returned by {} of contract {}: {}",
+                method, contract, message
+            ),
+        }
+    }
+}
+
+/// All the gas left except [`GAS_RESERVE`]
+pub fn default_gas_limit() -> Gas {
+    gas_left().saturating_sub(GAS_RESERVE)
+}
+
+#[doc(hidden)]
+pub fn serialize_args<A: Serialize>(method: &str, args: &A) -> Result<Vec<u8>, CallError> {
+    serde_json::to_vec(args).map_err(|e| CallError::InvalidArgs {
+        method: method.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Calls `method` of `contract` and deserializes the returned value
+pub fn call<R: DeserializeOwned>(
+    contract: &Address,
+    method: &str,
+    args: Vec<u8>,
+    read_only: bool,
+    gas_limit: Gas,
+) -> Result<R, CallError> {
+    let res = call_raw(contract, method, args, read_only, gas_limit)?;
+    if res.is_empty() {
+        return Err(CallError::EmptyResult {
+            contract: *contract,
+            method: method.to_string(),
+        });
+    }
+
+    serde_json::from_slice(&res).map_err(|e| CallError::InvalidResult {
+        contract: *contract,
+        method: method.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Calls `method` of `contract` that is expected to return nothing
+pub fn call_empty(
+    contract: &Address,
+    method: &str,
+    args: Vec<u8>,
+    read_only: bool,
+    gas_limit: Gas,
+) -> Result<(), CallError> {
+    let res = call_raw(contract, method, args, read_only, gas_limit)?;
+    if res.is_empty() {
+        Ok(())
+    } else {
+        Err(CallError::UnexpectedResult {
+            contract: *contract,
+            method: method.to_string(),
+        })
+    }
+}
+
+/// Calls `method` of `contract` and returns the raw result
+pub fn call_raw(
+    contract: &Address,
+    method: &str,
+    args: Vec<u8>,
+    read_only: bool,
+    gas_limit: Gas,
+) -> Result<Vec<u8>, CallError> {
+    let call = ContractCall {
+        contract_address: *contract,
+        method_name: method.to_string(),
+        args,
+        read_only,
+        gas_limit,
+    };
+
+    call_contract(&call).map_err(|message| CallError::CallFailed {
+        contract: *contract,
+        method: method.to_string(),
+        message,
+    })
+}
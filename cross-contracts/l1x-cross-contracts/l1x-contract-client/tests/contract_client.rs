@@ -0,0 +1,207 @@
+//! Calls made by a generated client against an in-memory host that records the calls and returns the queued
+//! results
+use std::{cell::RefCell, collections::HashMap, collections::VecDeque};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_contract_client::{contract_client, CallError, GAS_RESERVE};
+use l1x_sdk::types::{Address, Gas};
+
+const GAS_LEFT: Gas = 1_000_000;
+
+#[contract_client(CounterContract)]
+pub trait Counter {
+    /// Returns the counter of `owner`
+    fn get(&self, owner: String) -> u64;
+    fn increment(&mut self, owner: String, by: u64) -> u64;
+    fn reset(&mut self);
+}
+
+/// The `ContractCall` passed to the host
+#[derive(BorshDeserialize, Debug, PartialEq)]
+struct RecordedCall {
+    contract_address: Address,
+    method_name: String,
+    args: Vec<u8>,
+    read_only: bool,
+    gas_limit: Gas,
+}
+
+#[derive(Default)]
+struct Host {
+    registers: HashMap<u64, Vec<u8>>,
+    calls: Vec<RecordedCall>,
+    results: VecDeque<Result<Vec<u8>, String>>,
+}
+
+thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::default());
+}
+
+/// Queues the result of the next call
+fn push_result(result: Result<&str, &str>) {
+    let result = result
+        .map(|ok| ok.as_bytes().to_vec())
+        .map_err(String::from);
+    HOST.with(|host| host.borrow_mut().results.push_back(result));
+}
+
+fn take_calls() -> Vec<RecordedCall> {
+    HOST.with(|host| std::mem::take(&mut host.borrow_mut().calls))
+}
+
+#[no_mangle]
+extern "C" fn read_register(register_id: u64, result_addr: u64) {
+    HOST.with(|host| {
+        let data = &host.borrow().registers[&register_id];
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), result_addr as *mut u8, data.len()) }
+    });
+}
+
+#[no_mangle]
+extern "C" fn register_len(register_id: u64) -> u64 {
+    HOST.with(|host| {
+        host.borrow()
+            .registers
+            .get(&register_id)
+            .map_or(u64::MAX, |data| data.len() as u64)
+    })
+}
+
+#[no_mangle]
+extern "C" fn gas_left() -> u64 {
+    GAS_LEFT
+}
+
+#[no_mangle]
+extern "C" fn call_contract2(call_addr: u64, len: u64, register_id: u64) -> u64 {
+    let call = unsafe { std::slice::from_raw_parts(call_addr as *const u8, len as usize) };
+    let call = RecordedCall::try_from_slice(call).unwrap();
+    HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        host.calls.push(call);
+        let (status, data) = match host.results.pop_front().expect("No result is queued") {
+            Ok(data) => (1, data),
+            Err(message) => (0, message.into_bytes()),
+        };
+        host.registers.insert(register_id, data);
+        status
+    })
+}
+
+/// The SDK aborts through the host when a register is missing
+#[no_mangle]
+extern "C" fn panic() -> ! {
+    std::process::abort()
+}
+
+fn address() -> Address {
+    Address::from([7; 20])
+}
+
+#[test]
+fn read_only_call() {
+    push_result(Ok("5"));
+    assert_eq!(CounterContract::new(address()).get("alice".to_string()), 5);
+
+    assert_eq!(
+        take_calls(),
+        vec![RecordedCall {
+            contract_address: address(),
+            method_name: "get".to_string(),
+            args: br#"{"owner":"alice"}"#.to_vec(),
+            read_only: true,
+            gas_limit: GAS_LEFT - GAS_RESERVE,
+        }]
+    );
+}
+
+#[test]
+fn state_changing_call() {
+    push_result(Ok("6"));
+    let mut counter = CounterContract::new(address());
+    assert_eq!(counter.increment("alice".to_string(), 1), 6);
+
+    let calls = take_calls();
+    assert_eq!(calls[0].method_name, "increment");
+    assert_eq!(calls[0].args, br#"{"owner":"alice","by":1}"#.to_vec());
+    assert!(!calls[0].read_only);
+}
+
+#[test]
+fn unit_return() {
+    let mut counter = CounterContract::new(address());
+
+    push_result(Ok(""));
+    assert_eq!(counter.try_reset(), Ok(()));
+    assert_eq!(take_calls()[0].args, b"{}".to_vec());
+
+    push_result(Ok("null"));
+    assert_eq!(
+        counter.try_reset(),
+        Err(CallError::UnexpectedResult {
+            contract: address(),
+            method: "reset".to_string()
+        })
+    );
+}
+
+#[test]
+fn try_propagates_errors() {
+    let counter = CounterContract::new(address());
+
+    push_result(Err("Out of gas"));
+    assert_eq!(
+        counter.try_get("alice".to_string()),
+        Err(CallError::CallFailed {
+            contract: address(),
+            method: "get".to_string(),
+            message: "Out of gas".to_string()
+        })
+    );
+
+    push_result(Ok(""));
+    assert_eq!(
+        counter.try_get("alice".to_string()),
+        Err(CallError::EmptyResult {
+            contract: address(),
+            method: "get".to_string()
+        })
+    );
+
+    push_result(Ok(r#""five""#));
+    assert!(matches!(
+        counter.try_get("alice".to_string()),
+        Err(CallError::InvalidResult { .. })
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Got the error when called get of contract")]
+fn panics_on_errors() {
+    push_result(Err("Out of gas"));
+    CounterContract::new(address()).get("alice".to_string());
+}
+
+#[test]
+fn with_gas_limit() {
+    push_result(Ok("5"));
+    let counter = CounterContract::new(address()).with_gas_limit(30_000);
+    counter.get("alice".to_string());
+
+    assert_eq!(take_calls()[0].gas_limit, 30_000);
+}
+
+#[test]
+fn borsh_stores_only_the_address() {
+    let counter = CounterContract::new(address()).with_gas_limit(30_000);
+    let bytes = counter.try_to_vec().unwrap();
+    assert_eq!(bytes, address().try_to_vec().unwrap());
+
+    let restored = CounterContract::try_from_slice(&bytes).unwrap();
+    assert_eq!(restored.address(), address());
+
+    // The gas limit isn't stored, the restored client uses the default one
+    push_result(Ok("5"));
+    restored.get("alice".to_string());
+    assert_eq!(take_calls()[0].gas_limit, GAS_LEFT - GAS_RESERVE);
+}
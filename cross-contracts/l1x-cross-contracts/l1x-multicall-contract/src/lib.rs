@@ -0,0 +1,93 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_contract_client::{call_raw, default_gas_limit};
+use l1x_sdk::contract;
+use l1x_sdk::types::{Address, U64};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The maximum number of calls in one batch
+const MAX_CALLS: usize = 100;
+
+/// A call to make, `args` is the JSON object passed to the method. Missing `args` are the empty object
+#[derive(Deserialize)]
+pub struct Call {
+    contract_address: Address,
+    method_name: String,
+    #[serde(default)]
+    args: Value,
+    read_only: bool,
+    /// Without it the call gets an equal share of the gas left for the remaining calls
+    gas_limit: Option<U64>,
+}
+
+/// The outcome of a call. `result` is the returned JSON value on success, or the error message on failure
+#[derive(Serialize)]
+pub struct CallResult {
+    success: bool,
+    result: Value,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Contract {}
+
+#[contract]
+impl Contract {
+    pub fn new() {}
+
+    /// Makes `calls` in order and returns their results.
+    ///
+    /// If `allow_failure` is false, the first failed call reverts the whole batch
+    pub fn aggregate(calls: Vec<Call>, allow_failure: bool) -> Vec<CallResult> {
+        assert!(
+            calls.len() <= MAX_CALLS,
+            "Too many calls, the maximum is {}",
+            MAX_CALLS
+        );
+
+        let count = calls.len();
+        let mut results = Vec::with_capacity(count);
+        for (idx, call) in calls.into_iter().enumerate() {
+            let gas_limit = match call.gas_limit {
+                Some(gas_limit) => gas_limit.0,
+                None => default_gas_limit() / (count - idx) as u64,
+            };
+            let args = match call.args {
+                Value::Null => b"{}".to_vec(),
+                args => serde_json::to_vec(&args).expect("Can't serialize the call arguments"),
+            };
+
+            match call_raw(
+                &call.contract_address,
+                &call.method_name,
+                args,
+                call.read_only,
+                gas_limit,
+            ) {
+                Ok(res) => results.push(CallResult {
+                    success: true,
+                    result: Self::decode_result(&res),
+                }),
+                Err(e) => {
+                    assert!(allow_failure, "Call {} failed: {}", idx, e);
+                    results.push(CallResult {
+                        success: false,
+                        result: Value::String(e.to_string()),
+                    });
+                }
+            }
+        }
+
+        results
+    }
+}
+
+impl Contract {
+    /// An empty result is `null`, a result that is not JSON is returned as a string
+    fn decode_result(res: &[u8]) -> Value {
+        if res.is_empty() {
+            return Value::Null;
+        }
+        serde_json::from_slice(res)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(res).to_string()))
+    }
+}
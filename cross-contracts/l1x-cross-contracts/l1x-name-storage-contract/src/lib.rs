@@ -1,38 +1,234 @@
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use l1x_sdk::contract;
-use l1x_sdk::store::Vector;
+use l1x_sdk::store::{LookupMap, Vector};
+use l1x_sdk::types::Address;
+use l1x_sdk::{caller_address, contract_owner_address};
 
-pub struct Contract {}
+/// Key for the storage of the contract data.
+const STORAGE_CONTRACT_KEY: &[u8] = b"STATE";
+
+/// Key for the storage of the names by position.
+const STORAGE_NAMES_KEY: &[u8] = b"names";
+
+/// Key for the storage of the name records.
+const STORAGE_NAME_RECORDS_KEY: &[u8] = b"name-records";
+
+/// Key for the storage of the names owned by an address.
+const STORAGE_NAMES_BY_OWNER_KEY: &[u8] = b"names-by-owner";
+
+/// Key suffix for the storage of the positions of the names owned by an address.
+const STORAGE_OWNER_NAMES_KEY: &[u8] = b"owner-names";
+
+/// Key for the storage of the state version.
+const STORAGE_STATE_HEADER_KEY: &[u8] = b"state-header";
+
+/// Key for the storage of the names added before the state version, a `Vector<String>`.
+const STORAGE_LEGACY_NAMES_KEY: &[u8] = b"b";
+
+/// Key for the storage of the number of legacy names already migrated.
+const STORAGE_LEGACY_CURSOR_KEY: &[u8] = b"legacy-cursor";
+
+/// The names layout without a state header
+const LEGACY_STATE_VERSION: u32 = 1;
+const STATE_VERSION: u32 = 2;
+
+/// The maximum number of names returned by `get_names_paged`
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct NameRecord {
+    owner: Address,
+    /// Position in `Contract::names`
+    idx: u32,
+    /// Position in the names of the owner
+    owner_idx: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct StateHeader {
+    version: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Contract {
+    /// Position -> name. Positions are contiguous, removing a name moves the last one into its place
+    names: LookupMap<u32, String>,
+    names_count: u32,
+    records: LookupMap<String, NameRecord>,
+    /// Owner -> positions in `names` of the owned names. Removing a name moves the last one into its place
+    names_by_owner: LookupMap<Address, Vector<u32>>,
+}
 
 #[contract]
 impl Contract {
-    pub fn new() {}
+    pub fn new() {
+        let mut state = Self::empty();
+        state.save();
+        Self::save_state_version(STATE_VERSION);
+    }
 
-    pub fn add_name(name: String) {
-        let mut names = match l1x_sdk::storage_read(b"b") {
-            Some(vec) => Vector::try_from_slice(&vec).unwrap(),
+    /// Moves at most `limit` names added before the state version to the new layout and returns the number of
+    /// names left. The contract owner becomes the owner of the moved names, duplicates are skipped
+    pub fn migrate(limit: u32) -> u32 {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+        assert_eq!(
+            Self::state_version(),
+            LEGACY_STATE_VERSION,
+            "The contract state is already migrated"
+        );
+
+        let legacy: Vector<String> = match l1x_sdk::storage_read(STORAGE_LEGACY_NAMES_KEY) {
+            Some(bytes) => Vector::try_from_slice(&bytes).unwrap(),
             None => Vector::new(b"a".to_vec()),
         };
-        names.push(name);
-        names.flush();
-        l1x_sdk::storage_write(b"b", &borsh::to_vec(&names).unwrap());
+        let mut state = match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
+            None => Self::empty(),
+        };
+        let from = match l1x_sdk::storage_read(STORAGE_LEGACY_CURSOR_KEY) {
+            Some(bytes) => u32::try_from_slice(&bytes).unwrap(),
+            None => 0,
+        };
+        let to = from.saturating_add(limit).min(legacy.len());
+
+        let owner = contract_owner_address();
+        for idx in from..to {
+            let name = legacy.get(idx).expect("The position is checked above");
+            if !name.is_empty() && !state.records.contains_key(name) {
+                state.add_name_internal(name.clone(), owner);
+            }
+        }
+
+        state.save();
+        l1x_sdk::storage_write(STORAGE_LEGACY_CURSOR_KEY, &to.try_to_vec().unwrap());
+
+        let left = legacy.len() - to;
+        if left == 0 {
+            Self::save_state_version(STATE_VERSION);
+            l1x_sdk::msg(&format!(
+                "The contract state has been migrated to version {}",
+                STATE_VERSION
+            ));
+        }
+
+        left
     }
 
-    pub fn get_names() -> Vec<String> {
-        let names: Vector<String> =
-            Vector::try_from_slice(&l1x_sdk::storage_read(b"b").unwrap()).unwrap();
-        let mut result = Vec::new();
-        for i in 0..names.len() {
-            result.push(names[i].clone());
+    pub fn get_state_version() -> u32 {
+        Self::state_version()
+    }
+
+    /// Registers `name` for the caller. Names are unique
+    pub fn add_name(name: String) {
+        assert!(!name.is_empty(), "The name should not be empty");
+
+        let mut state = Self::load();
+        assert!(
+            !state.records.contains_key(&name),
+            "The name {} is already taken",
+            name
+        );
+
+        state.add_name_internal(name, caller_address());
+
+        state.save();
+    }
+
+    /// Removes `name`, only its owner can remove it
+    pub fn remove_name(name: String) {
+        let mut state = Self::load();
+
+        let record = state
+            .records
+            .remove(name.clone())
+            .unwrap_or_else(|| panic!("The name {} doesn't exist", name));
+        assert_eq!(
+            caller_address(),
+            record.owner,
+            "Only the owner can remove the name"
+        );
+
+        // Move the last name of the owner into the freed position of the owner names
+        let owned = state
+            .names_by_owner
+            .get_mut(&record.owner)
+            .expect("Can't find the owner names");
+        owned.swap_remove(record.owner_idx);
+        if let Some(moved_idx) = owned.get(record.owner_idx).copied() {
+            let moved = state
+                .names
+                .get(&moved_idx)
+                .expect("Can't find the moved name")
+                .clone();
+            state
+                .records
+                .get_mut(&moved)
+                .expect("Can't find the moved name")
+                .owner_idx = record.owner_idx;
         }
-        result
+
+        // Move the last name into the freed position
+        state.names_count -= 1;
+        let last = state
+            .names
+            .remove(state.names_count)
+            .expect("Can't find the last name");
+        if record.idx != state.names_count {
+            let moved = state
+                .records
+                .get_mut(&last)
+                .expect("Can't find the moved name");
+            moved.idx = record.idx;
+            let (moved_owner, moved_owner_idx) = (moved.owner, moved.owner_idx);
+            state
+                .names_by_owner
+                .get_mut(&moved_owner)
+                .expect("Can't find the owner names")
+                .set(moved_owner_idx, record.idx);
+            state.names.insert(record.idx, last);
+        }
+
+        state.save();
+    }
+
+    pub fn get_names() -> Vec<String> {
+        let state = Self::load();
+        Self::names_range(&state, 0, state.names_count)
+    }
+
+    /// Returns at most `limit` names starting from the position `from`
+    pub fn get_names_paged(from: u32, limit: u32) -> Vec<String> {
+        let state = Self::load();
+        Self::names_range(&state, from, limit.min(MAX_PAGE_SIZE))
+    }
+
+    pub fn get_names_count() -> u32 {
+        Self::load().names_count
+    }
+
+    pub fn get_names_of(owner: Address) -> Vec<String> {
+        let state = Self::load();
+        let owned = match state.names_by_owner.get(&owner) {
+            Some(owned) => owned,
+            None => return Vec::new(),
+        };
+        (0..owned.len())
+            .filter_map(|owner_idx| owned.get(owner_idx))
+            .filter_map(|idx| state.names.get(idx).cloned())
+            .collect()
+    }
+
+    pub fn get_name_owner(name: String) -> Option<Address> {
+        Self::load().records.get(&name).map(|record| record.owner)
     }
 
     pub fn hello() {
-        let names: Vector<String> =
-            Vector::try_from_slice(&l1x_sdk::storage_read(b"b").unwrap()).unwrap();
-        for i in 0..names.len() {
-            l1x_sdk::msg(&format!("Hello, {}!", names[i]));
+        for name in Self::get_names() {
+            l1x_sdk::msg(&format!("Hello, {}!", name));
         }
     }
 
@@ -45,3 +241,77 @@ impl Contract {
         ));
     }
 }
+
+impl Contract {
+    fn add_name_internal(&mut self, name: String, owner: Address) {
+        if !self.names_by_owner.contains_key(&owner) {
+            let prefix = [owner.to_vec().as_slice(), STORAGE_OWNER_NAMES_KEY].concat();
+            self.names_by_owner.insert(owner, Vector::new(prefix));
+        }
+        let owned = self
+            .names_by_owner
+            .get_mut(&owner)
+            .expect("The owner names are inserted above");
+        owned.push(self.names_count);
+
+        self.records.insert(
+            name.clone(),
+            NameRecord {
+                owner,
+                idx: self.names_count,
+                owner_idx: owned.len() - 1,
+            },
+        );
+        self.names.insert(self.names_count, name);
+        self.names_count = self
+            .names_count
+            .checked_add(1)
+            .expect("Names count overflowed");
+    }
+
+    fn names_range(&self, from: u32, limit: u32) -> Vec<String> {
+        let to = from.saturating_add(limit).min(self.names_count);
+        (from..to)
+            .filter_map(|idx| self.names.get(&idx).cloned())
+            .collect()
+    }
+
+    fn empty() -> Self {
+        Self {
+            names: LookupMap::new(STORAGE_NAMES_KEY.to_vec()),
+            names_count: 0,
+            records: LookupMap::new(STORAGE_NAME_RECORDS_KEY.to_vec()),
+            names_by_owner: LookupMap::new(STORAGE_NAMES_BY_OWNER_KEY.to_vec()),
+        }
+    }
+
+    fn state_version() -> u32 {
+        match l1x_sdk::storage_read(STORAGE_STATE_HEADER_KEY) {
+            Some(bytes) => StateHeader::try_from_slice(&bytes).unwrap().version,
+            None => LEGACY_STATE_VERSION,
+        }
+    }
+
+    fn save_state_version(version: u32) {
+        l1x_sdk::storage_write(
+            STORAGE_STATE_HEADER_KEY,
+            &StateHeader { version }.try_to_vec().unwrap(),
+        );
+    }
+
+    fn load() -> Self {
+        assert_eq!(
+            Self::state_version(),
+            STATE_VERSION,
+            "The contract state is outdated, call `migrate` first"
+        );
+        match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
+            None => panic!("The contract isn't initialized"),
+        }
+    }
+
+    fn save(&mut self) {
+        l1x_sdk::storage_write(STORAGE_CONTRACT_KEY, &self.try_to_vec().unwrap());
+    }
+}
@@ -1,14 +1,19 @@
 use borsh::BorshDeserialize;
 use borsh::BorshSerialize;
-use l1x_sdk::call_contract;
+use l1x_contract_client::contract_client;
 use l1x_sdk::contract;
-use l1x_sdk::contract_interaction::ContractCall;
 use l1x_sdk::emit_event_experimental;
 use l1x_sdk::types::Address;
-use serde::Serialize;
 
 const STORAGE_CONTRACT_KEY: &[u8] = b"STATE";
 
+/// The methods of the name storage contract called by this contract
+#[contract_client(NameStorageContract)]
+trait NameStorage {
+    fn get_names(&self) -> Vec<String>;
+    fn add_name(&mut self, name: String);
+}
+
 #[derive(BorshSerialize)]
 struct Event {
     name: String,
@@ -49,17 +54,10 @@ impl Contract {
 
     pub fn hello() {
         let address = Self::load().contract_instance_address;
-        let call = ContractCall {
-            contract_address: address.clone(),
-            method_name: "get_names".to_string(),
-            args: "{}".as_bytes().to_vec(),
-            read_only: true,
-            gas_limit: 10000,
-        };
+        let names = NameStorageContract::new(address).with_gas_limit(10_000);
 
-        match call_contract(&call) {
+        match names.try_get_names() {
             Ok(res) => {
-                let res: Result<Vec<String>, serde_json::Error> = serde_json::from_slice(&res);
                 l1x_sdk::msg(&format!("Returned by the external contract: {:?}", res));
             }
             Err(e) => {
@@ -70,31 +68,11 @@ impl Contract {
 
     pub fn add_name(name: String) {
         let address = Self::load().contract_instance_address;
+        let mut names = NameStorageContract::new(address).with_gas_limit(30_000);
 
-        let args = {
-            #[derive(Serialize)]
-            struct Args {
-                name: String,
-            }
-            Args { name }
-        };
-
-        let call = ContractCall {
-            contract_address: address.clone(),
-            method_name: "add_name".to_string(),
-            args: serde_json::to_vec(&args).unwrap(),
-            read_only: false,
-            gas_limit: 30000,
-        };
-
-        match call_contract(&call) {
-            Ok(res) => {
-                if res.is_empty() {
-                    l1x_sdk::msg(&format!("The external contract returned the empty result"));
-                } else {
-                    let res: Result<Vec<String>, serde_json::Error> = serde_json::from_slice(&res);
-                    l1x_sdk::msg(&format!("Returned by the external contract: {:?}", res));
-                }
+        match names.try_add_name(name) {
+            Ok(()) => {
+                l1x_sdk::msg("The external contract added the name");
             }
             Err(e) => {
                 l1x_sdk::msg(&format!("The external contract failed with the error: {e}"));
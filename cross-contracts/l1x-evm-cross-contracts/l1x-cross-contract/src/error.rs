@@ -0,0 +1,207 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// Why an EVM call failed
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum EvmCallError {
+    /// The call reverted with `Error(string)`, `require(condition, reason)` or `revert(reason)`
+    Reverted { reason: String },
+    /// The call reverted with `Panic(uint256)`, for example on an arithmetic overflow
+    Panicked { code: String, description: String },
+    /// The call reverted with a custom error or without data, `data` is hex encoded
+    RevertedWithData { data: String },
+    /// The host couldn't execute the call
+    CallFailed { message: String },
+    /// The call returned something that doesn't match the function outputs
+    InvalidOutput { message: String },
+}
+
+impl fmt::Display for EvmCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reverted { reason } => write!(f, "EVM call reverted: {}", reason),
+            Self::Panicked { code, description } => {
+                write!(f, "EVM call panicked with code {}: {}", code, description)
+            }
+            Self::RevertedWithData { data } => write!(f, "EVM call reverted with data 0x{}", data),
+            Self::CallFailed { message } => write!(f, "EVM call failed: {}", message),
+            Self::InvalidOutput { message } => write!(f, "Invalid EVM call output: {}", message),
+        }
+    }
+}
+
+impl EvmCallError {
+    /// Decodes a Solidity revert payload
+    fn from_revert_data(data: &[u8]) -> Self {
+        let error: solabi::FunctionEncoder<(String,), ()> =
+            solabi::FunctionEncoder::new(solabi::selector!("Error(string)"));
+        let panic: solabi::FunctionEncoder<(solabi::U256,), ()> =
+            solabi::FunctionEncoder::new(solabi::selector!("Panic(uint256)"));
+
+        if let Ok((reason,)) = error.decode_params(data) {
+            Self::Reverted { reason }
+        } else if let Ok((code,)) = panic.decode_params(data) {
+            Self::Panicked {
+                code: format!("{:#x}", code),
+                description: panic_description(code).to_string(),
+            }
+        } else {
+            Self::RevertedWithData {
+                data: hex::encode(data),
+            }
+        }
+    }
+
+    /// Builds the error from the message returned by `call_contract`.
+    ///
+    /// This is a best-effort guess: neither the SDK nor the host documents the format of the message. If it
+    /// contains a hex encoded `Error(string)` or `Panic(uint256)` payload, the payload is decoded, otherwise the
+    /// message is kept as it is
+    pub fn from_call_error(message: String) -> Self {
+        match find_revert_payload(&message) {
+            Some(data) => Self::from_revert_data(&data),
+            None => Self::CallFailed { message },
+        }
+    }
+}
+
+/// Finds a hex encoded `Error(string)` or `Panic(uint256)` payload in `message`
+fn find_revert_payload(message: &str) -> Option<Vec<u8>> {
+    let message = message.to_ascii_lowercase();
+    [
+        solabi::selector!("Error(string)"),
+        solabi::selector!("Panic(uint256)"),
+    ]
+    .iter()
+    .find_map(|selector| {
+        let start = message.find(&hex::encode(selector.0))?;
+        let payload: String = message[start..]
+            .chars()
+            .take_while(char::is_ascii_hexdigit)
+            .collect();
+        hex::decode(payload).ok()
+    })
+}
+
+/// The meaning of the Solidity panic codes
+fn panic_description(code: solabi::U256) -> &'static str {
+    let bytes = code.to_be_bytes();
+    if bytes[..31].iter().any(|byte| *byte != 0) {
+        return "Unknown panic code";
+    }
+    match bytes[31] {
+        0x00 => "Generic compiler inserted panic",
+        0x01 => "Assertion failed",
+        0x11 => "Arithmetic overflow or underflow",
+        0x12 => "Division or modulo by zero",
+        0x21 => "Invalid enum value",
+        0x22 => "Incorrectly encoded storage byte array",
+        0x31 => "pop() on an empty array",
+        0x32 => "Array index out of bounds",
+        0x41 => "Too much memory allocated",
+        0x51 => "Call to a zero-initialized internal function",
+        _ => "Unknown panic code",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERROR_SELECTOR: &str = "08c379a0";
+    const PANIC_SELECTOR: &str = "4e487b71";
+
+    fn word(value: usize) -> String {
+        format!("{:064x}", value)
+    }
+
+    /// The hex encoded `Error(string)` payload of `revert(reason)`
+    fn error_payload(reason: &str) -> String {
+        let mut data = hex::encode(reason);
+        data.push_str(&"0".repeat((64 - data.len() % 64) % 64));
+        format!(
+            "{}{}{}{}",
+            ERROR_SELECTOR,
+            word(32),
+            word(reason.len()),
+            data
+        )
+    }
+
+    #[test]
+    fn decodes_error_string() {
+        assert_eq!(
+            EvmCallError::from_call_error(error_payload("Not enough")),
+            EvmCallError::Reverted {
+                reason: "Not enough".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_panic_code() {
+        assert_eq!(
+            EvmCallError::from_call_error(format!("0x{}{}", PANIC_SELECTOR, word(0x11))),
+            EvmCallError::Panicked {
+                code: "0x11".to_string(),
+                description: "Arithmetic overflow or underflow".to_string()
+            }
+        );
+        assert_eq!(
+            EvmCallError::from_call_error(format!("{}{}", PANIC_SELECTOR, word(0x99))),
+            EvmCallError::Panicked {
+                code: "0x99".to_string(),
+                description: "Unknown panic code".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_payload_inside_message() {
+        let message = format!(
+            "execution reverted: 0x{}",
+            error_payload("Paused").to_uppercase()
+        );
+        assert_eq!(
+            EvmCallError::from_call_error(message),
+            EvmCallError::Reverted {
+                reason: "Paused".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_other_messages() {
+        for message in [
+            "Out of gas".to_string(),
+            // A custom error
+            format!("0xcafebabe{}", word(1)),
+        ] {
+            assert_eq!(
+                EvmCallError::from_call_error(message.clone()),
+                EvmCallError::CallFailed { message }
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_malformed_payload_data() {
+        let payload = error_payload("Not enough")[..136].to_string();
+        assert_eq!(
+            EvmCallError::from_call_error(payload.clone()),
+            EvmCallError::RevertedWithData { data: payload }
+        );
+    }
+
+    #[test]
+    fn keeps_custom_error_data() {
+        let data = hex::decode(format!("cafebabe{}", word(1))).unwrap();
+        assert_eq!(
+            EvmCallError::from_revert_data(&data),
+            EvmCallError::RevertedWithData {
+                data: hex::encode(&data)
+            }
+        );
+    }
+}
@@ -1,13 +1,54 @@
+mod error;
+
 use borsh::BorshDeserialize;
 use borsh::BorshSerialize;
+use error::EvmCallError;
 use l1x_sdk::call_contract;
 use l1x_sdk::contract;
 use l1x_sdk::contract_interaction::ContractCall;
-use l1x_sdk::types::{Address, U128};
-use solabi;
+use l1x_sdk::types::{Address, Gas, U128};
+use l1x_sdk::{caller_address, contract_owner_address};
 
 const STORAGE_CONTRACT_KEY: &[u8] = b"STATE";
 
+/// Gas kept for the caller when a call doesn't set its gas limit
+const EVM_CALL_GAS_RESERVE: Gas = 10_000;
+
+/// Calls the EVM contract at `contract_address` with ABI encoded call data and returns the ABI encoded result
+fn execute_evm(
+    contract_address: Address,
+    args: Vec<u8>,
+    read_only: bool,
+    gas_limit: Option<Gas>,
+) -> Result<Vec<u8>, EvmCallError> {
+    l1x_sdk::msg(&format!("L1XVM: HEX_ARG: {}", hex::encode(&args)));
+
+    let call = ContractCall {
+        contract_address,
+        method_name: "".to_string(), // method_name is not used in case of EVM call
+        args,
+        read_only,
+        gas_limit: gas_limit
+            .unwrap_or_else(|| l1x_sdk::gas_left().saturating_sub(EVM_CALL_GAS_RESERVE)),
+    };
+
+    call_contract(&call).map_err(EvmCallError::from_call_error)
+}
+
+fn to_evm_address(address: Address) -> solabi::Address {
+    solabi::Address(*address.as_bytes())
+}
+
+/// Converts an EVM `uint256` into `u128`, fails if the value doesn't fit
+fn u256_to_u128(value: solabi::U256) -> Result<u128, String> {
+    let bytes = value.to_be_bytes();
+    let (high, low) = bytes.split_at(16);
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(format!("{} doesn't fit in u128", value));
+    }
+    Ok(u128::from_be_bytes(low.try_into().unwrap()))
+}
+
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
 struct EvmErc20 {
     evm_contract_address: Address,
@@ -20,44 +61,127 @@ impl EvmErc20 {
         }
     }
 
-    pub fn get_value(&self) -> solabi::U256 {
+    pub fn get_value(&self) -> Result<solabi::U256, EvmCallError> {
         let func: solabi::FunctionEncoder<(), (solabi::U256,)> =
             solabi::FunctionEncoder::new(solabi::selector!("getValue()"));
 
-        self.call_evm(&func, &(), true).0
+        self.call_evm(&func, &(), true, None).map(|ret| ret.0)
     }
 
-  
-    pub fn set_value(&self, _data: solabi::U256) -> bool {
+    pub fn set_value(&self, _data: solabi::U256) -> Result<bool, EvmCallError> {
         let func: solabi::FunctionEncoder<solabi::U256, (bool,)> =
             solabi::FunctionEncoder::new(solabi::selector!("setValue(uint256)"));
 
-        self.call_evm(&func, &(_data), false).0
+        self.call_evm(&func, &(_data), false, None).map(|ret| ret.0)
+    }
+
+    pub fn name(&self) -> Result<String, EvmCallError> {
+        let func: solabi::FunctionEncoder<(), (String,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("name()"));
+
+        self.call_evm(&func, &(), true, None).map(|ret| ret.0)
+    }
+
+    pub fn symbol(&self) -> Result<String, EvmCallError> {
+        let func: solabi::FunctionEncoder<(), (String,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("symbol()"));
+
+        self.call_evm(&func, &(), true, None).map(|ret| ret.0)
+    }
+
+    pub fn decimals(&self) -> Result<u8, EvmCallError> {
+        let func: solabi::FunctionEncoder<(), (u8,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("decimals()"));
+
+        self.call_evm(&func, &(), true, None).map(|ret| ret.0)
+    }
+
+    pub fn total_supply(&self) -> Result<solabi::U256, EvmCallError> {
+        let func: solabi::FunctionEncoder<(), (solabi::U256,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("totalSupply()"));
+
+        self.call_evm(&func, &(), true, None).map(|ret| ret.0)
+    }
+
+    pub fn balance_of(&self, owner: solabi::Address) -> Result<solabi::U256, EvmCallError> {
+        let func: solabi::FunctionEncoder<(solabi::Address,), (solabi::U256,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("balanceOf(address)"));
+
+        self.call_evm(&func, &(owner,), true, None).map(|ret| ret.0)
+    }
+
+    pub fn transfer(
+        &self,
+        to: solabi::Address,
+        amount: solabi::U256,
+    ) -> Result<bool, EvmCallError> {
+        let func: solabi::FunctionEncoder<(solabi::Address, solabi::U256), (bool,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("transfer(address,uint256)"));
+
+        self.call_evm(&func, &(to, amount), false, None)
+            .map(|ret| ret.0)
+    }
+
+    pub fn approve(
+        &self,
+        spender: solabi::Address,
+        amount: solabi::U256,
+    ) -> Result<bool, EvmCallError> {
+        let func: solabi::FunctionEncoder<(solabi::Address, solabi::U256), (bool,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("approve(address,uint256)"));
+
+        self.call_evm(&func, &(spender, amount), false, None)
+            .map(|ret| ret.0)
     }
 
-   
+    pub fn allowance(
+        &self,
+        owner: solabi::Address,
+        spender: solabi::Address,
+    ) -> Result<solabi::U256, EvmCallError> {
+        let func: solabi::FunctionEncoder<(solabi::Address, solabi::Address), (solabi::U256,)> =
+            solabi::FunctionEncoder::new(solabi::selector!("allowance(address,address)"));
+
+        self.call_evm(&func, &(owner, spender), true, None)
+            .map(|ret| ret.0)
+    }
 
-    fn call_evm<P, R>(&self, func: &solabi::FunctionEncoder<P, R>, params: &P, read_only: bool) -> R
+    pub fn transfer_from(
+        &self,
+        from: solabi::Address,
+        to: solabi::Address,
+        amount: solabi::U256,
+    ) -> Result<bool, EvmCallError> {
+        let func: solabi::FunctionEncoder<
+            (solabi::Address, solabi::Address, solabi::U256),
+            (bool,),
+        > = solabi::FunctionEncoder::new(solabi::selector!(
+            "transferFrom(address,address,uint256)"
+        ));
+
+        self.call_evm(&func, &(from, to, amount), false, None)
+            .map(|ret| ret.0)
+    }
+
+    fn call_evm<P, R>(
+        &self,
+        func: &solabi::FunctionEncoder<P, R>,
+        params: &P,
+        read_only: bool,
+        gas_limit: Option<Gas>,
+    ) -> Result<R, EvmCallError>
     where
         P: solabi::encode::Encode + solabi::decode::Decode,
         R: solabi::encode::Encode + solabi::decode::Decode,
     {
         let args = func.encode_params(params);
 
-        l1x_sdk::msg(&format!("L1XVM: HEX_ARG: {}", hex::encode(&args)));
-
-        let call = ContractCall {
-            contract_address: self.evm_contract_address.clone(),
-            method_name: "".to_string(), // method_name is not used in case of EVM call
-            args,
-            read_only,
-            gas_limit: 12,
-        };
-
-        let ret = call_contract(&call).expect("Function returned nothing");
+        let ret = execute_evm(self.evm_contract_address, args, read_only, gas_limit)?;
 
         func.decode_returns(&ret)
-            .unwrap_or_else(|e| panic!("err: {}", e.to_string()))
+            .map_err(|e| EvmCallError::InvalidOutput {
+                message: e.to_string(),
+            })
     }
 }
 
@@ -75,32 +199,147 @@ impl Contract {
         Self::save(&mut contract);
     }
 
-    pub fn get_value() -> String{
-        l1x_sdk::msg(&format!("L1XVM: getValue"));
+    pub fn get_value() -> String {
+        Self::try_get_value().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `get_value`, but returns the decoded revert reason instead of failing
+    pub fn try_get_value() -> Result<String, EvmCallError> {
+        l1x_sdk::msg("L1XVM: getValue");
+
+        let contract = Self::load();
+        let ret = contract.evm_erc20.get_value()?.to_string();
+
+        l1x_sdk::msg(&format!("L1XVM: getValue returns {}", ret));
+        Ok(ret)
+    }
+
+    pub fn set_value(data: U128) {
+        l1x_sdk::msg(&format!("L1XVM: setValue init {:?}", data));
+
+        let contract = Self::load();
+
+        contract
+            .evm_erc20
+            .set_value(solabi::U256::from(data.0))
+            .unwrap_or_else(|e| panic!("{}", e));
+        l1x_sdk::msg(&format!("L1XVM: setValue done {:?}", data));
+    }
+
+    pub fn erc20_name() -> String {
+        Self::load()
+            .evm_erc20
+            .name()
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn erc20_symbol() -> String {
+        Self::load()
+            .evm_erc20
+            .symbol()
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn erc20_decimals() -> u8 {
+        Self::load()
+            .evm_erc20
+            .decimals()
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn erc20_total_supply() -> U128 {
+        let total_supply = Self::load()
+            .evm_erc20
+            .total_supply()
+            .unwrap_or_else(|e| panic!("{}", e));
+        u256_to_u128(total_supply)
+            .unwrap_or_else(|e| panic!("{}", e))
+            .into()
+    }
+
+    pub fn erc20_balance_of(owner: Address) -> U128 {
+        let balance = Self::load()
+            .evm_erc20
+            .balance_of(to_evm_address(owner))
+            .unwrap_or_else(|e| panic!("{}", e));
+        u256_to_u128(balance)
+            .unwrap_or_else(|e| panic!("{}", e))
+            .into()
+    }
+
+    pub fn erc20_allowance(owner: Address, spender: Address) -> U128 {
+        let allowance = Self::load()
+            .evm_erc20
+            .allowance(to_evm_address(owner), to_evm_address(spender))
+            .unwrap_or_else(|e| panic!("{}", e));
+        u256_to_u128(allowance)
+            .unwrap_or_else(|e| panic!("{}", e))
+            .into()
+    }
+
+    /// Transfers the tokens held by this contract on the EVM side. Only the contract owner can spend them
+    pub fn erc20_transfer(to: Address, amount: U128) -> bool {
+        Self::assert_owner();
 
         let contract = Self::load();
         let ret = contract
             .evm_erc20
-            .get_value()
-            .to_string();
+            .transfer(to_evm_address(to), solabi::U256::from(amount.0))
+            .unwrap_or_else(|e| panic!("{}", e));
 
-        l1x_sdk::msg(&format!("L1XVM: getValue returns {}",  ret));
+        l1x_sdk::msg(&format!(
+            "L1XVM: transfer {:?} to {} returns {}",
+            amount, to, ret
+        ));
         ret
     }
 
-    pub fn set_value(data: U128) {
+    /// Approves `spender` to spend the tokens held by this contract on the EVM side. Only the contract owner can
+    /// approve
+    pub fn erc20_approve(spender: Address, amount: U128) -> bool {
+        Self::assert_owner();
+
+        let contract = Self::load();
+        let ret = contract
+            .evm_erc20
+            .approve(to_evm_address(spender), solabi::U256::from(amount.0))
+            .unwrap_or_else(|e| panic!("{}", e));
+
         l1x_sdk::msg(&format!(
-            "L1XVM: setValue init {:?}", data
+            "L1XVM: approve {:?} for {} returns {}",
+            amount, spender, ret
         ));
+        ret
+    }
+
+    /// Transfers the tokens `from` approved to this contract on the EVM side. Only the contract owner can spend
+    /// the allowance
+    pub fn erc20_transfer_from(from: Address, to: Address, amount: U128) -> bool {
+        Self::assert_owner();
 
         let contract = Self::load();
+        let ret = contract
+            .evm_erc20
+            .transfer_from(
+                to_evm_address(from),
+                to_evm_address(to),
+                solabi::U256::from(amount.0),
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
 
-        contract.evm_erc20.set_value(
-            solabi::U256::from(data.0),
-        );
         l1x_sdk::msg(&format!(
-            "L1XVM: setValue done {:?}", data
+            "L1XVM: transferFrom {:?} from {} to {} returns {}",
+            amount, from, to, ret
         ));
+        ret
+    }
+
+    fn assert_owner() {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
     }
 
     fn load() -> Self {
@@ -0,0 +1,711 @@
+//! Runtime ABI encoding for EVM functions described by a signature string.
+//!
+//! Values are represented as JSON: integers as decimal strings (`"0x"` prefixed hex strings, `"-0x"`
+//! for negative ones, and JSON numbers are accepted too), addresses, `bytes` and `bytesN` as `"0x"`
+//! prefixed hex strings, arrays and tuples as JSON arrays.
+use serde_json::Value;
+use solabi::{I256, U256};
+use tiny_keccak::{Hasher, Keccak};
+
+const WORD_SIZE: usize = 32;
+
+pub type Word = [u8; WORD_SIZE];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamType {
+    Uint(usize),
+    Int(usize),
+    Address,
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+
+        if let Some(prefix) = value.strip_suffix(']') {
+            let open = prefix
+                .rfind('[')
+                .ok_or_else(|| format!("Invalid type {}", value))?;
+            let inner = Box::new(Self::parse(&prefix[..open])?);
+            let size = &prefix[open + 1..];
+            return if size.is_empty() {
+                Ok(Self::Array(inner))
+            } else {
+                let size = size
+                    .parse()
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .ok_or_else(|| format!("Invalid array size in {}", value))?;
+                Ok(Self::FixedArray(inner, size))
+            };
+        }
+
+        if let Some(inner) = value.strip_prefix('(') {
+            let inner = inner
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Invalid tuple {}", value))?;
+            return Ok(Self::Tuple(parse_params(inner)?));
+        }
+
+        match value {
+            "address" => Ok(Self::Address),
+            "bool" => Ok(Self::Bool),
+            "string" => Ok(Self::String),
+            "bytes" => Ok(Self::Bytes),
+            "uint" => Ok(Self::Uint(256)),
+            "int" => Ok(Self::Int(256)),
+            _ => {
+                let (kind, size) = value
+                    .find(|c: char| c.is_ascii_digit())
+                    .map(|idx| value.split_at(idx))
+                    .ok_or_else(|| format!("Unknown type {}", value))?;
+                let size: usize = size
+                    .parse()
+                    .map_err(|_| format!("Unknown type {}", value))?;
+                let int_size = (8..=256).step_by(8).any(|bits| bits == size);
+                match kind {
+                    "uint" if int_size => Ok(Self::Uint(size)),
+                    "int" if int_size => Ok(Self::Int(size)),
+                    "bytes" if (1..=32).contains(&size) => Ok(Self::FixedBytes(size)),
+                    _ => Err(format!("Unknown type {}", value)),
+                }
+            }
+        }
+    }
+
+    /// The type as it appears in the canonical function signature
+    pub fn canonical(&self) -> String {
+        match self {
+            Self::Uint(bits) => format!("uint{}", bits),
+            Self::Int(bits) => format!("int{}", bits),
+            Self::Address => "address".to_string(),
+            Self::Bool => "bool".to_string(),
+            Self::FixedBytes(size) => format!("bytes{}", size),
+            Self::Bytes => "bytes".to_string(),
+            Self::String => "string".to_string(),
+            Self::Array(inner) => format!("{}[]", inner.canonical()),
+            Self::FixedArray(inner, size) => format!("{}[{}]", inner.canonical(), size),
+            Self::Tuple(types) => format!("({})", canonical_list(types)),
+        }
+    }
+
+    pub(crate) fn is_dynamic(&self) -> bool {
+        match self {
+            Self::Bytes | Self::String | Self::Array(_) => true,
+            Self::FixedArray(inner, _) => inner.is_dynamic(),
+            Self::Tuple(types) => types.iter().any(Self::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Size of the value in the head of the enclosing tuple
+    fn head_size(&self) -> usize {
+        if self.is_dynamic() {
+            return WORD_SIZE;
+        }
+        match self {
+            Self::FixedArray(inner, size) => inner.head_size() * size,
+            Self::Tuple(types) => types.iter().map(Self::head_size).sum(),
+            _ => WORD_SIZE,
+        }
+    }
+}
+
+/// An EVM function parsed from `name(inputs)` optionally followed by `(outputs)` or `returns (outputs)`,
+/// for example `balanceOf(address)(uint256)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    pub inputs: Vec<ParamType>,
+    pub outputs: Vec<ParamType>,
+}
+
+impl Signature {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let value = value.strip_prefix("function ").unwrap_or(value);
+
+        let open = value
+            .find('(')
+            .ok_or_else(|| format!("Invalid signature {}", value))?;
+        let name = value[..open].trim();
+        if name.is_empty() {
+            return Err(format!("Invalid signature {}", value));
+        }
+
+        let close = open
+            + closing_paren(&value[open..])
+                .ok_or_else(|| format!("Invalid signature {}", value))?;
+        let inputs = parse_params(&value[open + 1..close])?;
+
+        let rest = value[close + 1..].trim();
+        let rest = rest.strip_prefix("returns").unwrap_or(rest).trim();
+        let outputs = if rest.is_empty() {
+            Vec::new()
+        } else {
+            match ParamType::parse(rest)? {
+                ParamType::Tuple(outputs) => outputs,
+                _ => return Err(format!("Invalid outputs in {}", value)),
+            }
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            inputs,
+            outputs,
+        })
+    }
+
+    /// The first 4 bytes of the keccak256 hash of the canonical signature
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = keccak256(format!("{}({})", self.name, canonical_list(&self.inputs)).as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Encodes the JSON `args` as call data, prefixed with the selector
+    pub fn encode_call(&self, args: &[Value]) -> Result<Vec<u8>, String> {
+        let mut data = self.selector().to_vec();
+        data.extend(encode(&self.inputs, args)?);
+        Ok(data)
+    }
+
+    /// Prefixes ABI encoded arguments with the selector
+    pub fn encode_raw_call(&self, args: &[u8]) -> Vec<u8> {
+        let mut data = self.selector().to_vec();
+        data.extend_from_slice(args);
+        data
+    }
+
+    pub fn decode_returns(&self, data: &[u8]) -> Result<Vec<Value>, String> {
+        decode(&self.outputs, data)
+    }
+}
+
+pub fn keccak256(data: &[u8]) -> Word {
+    let mut hasher = Keccak::v256();
+    let mut output = Word::default();
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+/// ABI encodes `values` as a tuple of `types`
+pub fn encode(types: &[ParamType], values: &[Value]) -> Result<Vec<u8>, String> {
+    if types.len() != values.len() {
+        return Err(format!(
+            "Expected {} values, got {}",
+            types.len(),
+            values.len()
+        ));
+    }
+
+    let head_size: usize = types.iter().map(ParamType::head_size).sum();
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+    for (kind, value) in types.iter().zip(values) {
+        let encoded = encode_value(kind, value)?;
+        if kind.is_dynamic() {
+            head.extend(usize_word(head_size + tail.len()));
+            tail.extend(encoded);
+        } else {
+            head.extend(encoded);
+        }
+    }
+
+    head.extend(tail);
+    Ok(head)
+}
+
+/// Decodes a tuple of `types` from ABI encoded `data`
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Value>, String> {
+    let mut values = Vec::with_capacity(types.len());
+    let mut offset = 0;
+    for kind in types {
+        let value = if kind.is_dynamic() {
+            let tail = read_usize(data, offset)?;
+            decode_value(kind, data.get(tail..).ok_or("Offset out of bounds")?)?
+        } else {
+            decode_value(kind, data.get(offset..).ok_or("Offset out of bounds")?)?
+        };
+        values.push(value);
+        offset += kind.head_size();
+    }
+
+    Ok(values)
+}
+
+fn encode_value(kind: &ParamType, value: &Value) -> Result<Vec<u8>, String> {
+    match kind {
+        ParamType::Uint(bits) => {
+            let word = parse_uint(value)?.to_be_bytes();
+            if word[..WORD_SIZE - bits / 8].iter().any(|byte| *byte != 0) {
+                return Err(format!("{} doesn't fit in uint{}", value, bits));
+            }
+            Ok(word.to_vec())
+        }
+        ParamType::Int(bits) => {
+            let int = parse_int(value)?;
+            let word = int.to_be_bytes();
+            let (padding, significant) = word.split_at(WORD_SIZE - bits / 8);
+            let sign = if int.is_negative() { 0xff } else { 0 };
+            if padding.iter().any(|byte| *byte != sign)
+                || (significant[0] & 0x80 != 0) != int.is_negative()
+            {
+                return Err(format!("{} doesn't fit in int{}", value, bits));
+            }
+            Ok(word.to_vec())
+        }
+        ParamType::Address => {
+            let address = parse_hex(value)?;
+            if address.len() != 20 {
+                return Err(format!("{} is not a 20-byte address", value));
+            }
+            let mut word = Word::default();
+            word[12..].copy_from_slice(&address);
+            Ok(word.to_vec())
+        }
+        ParamType::Bool => {
+            let flag = value
+                .as_bool()
+                .ok_or_else(|| format!("{} is not a bool", value))?;
+            Ok(usize_word(flag as usize).to_vec())
+        }
+        ParamType::FixedBytes(size) => {
+            let bytes = parse_hex(value)?;
+            if bytes.len() != *size {
+                return Err(format!("{} is not {} bytes long", value, size));
+            }
+            let mut word = Word::default();
+            word[..*size].copy_from_slice(&bytes);
+            Ok(word.to_vec())
+        }
+        ParamType::Bytes => Ok(encode_bytes(&parse_hex(value)?)),
+        ParamType::String => {
+            let string = value
+                .as_str()
+                .ok_or_else(|| format!("{} is not a string", value))?;
+            Ok(encode_bytes(string.as_bytes()))
+        }
+        ParamType::Array(inner) => {
+            let items = as_array(value)?;
+            let mut encoded = usize_word(items.len()).to_vec();
+            encoded.extend(encode(&vec![(**inner).clone(); items.len()], items)?);
+            Ok(encoded)
+        }
+        ParamType::FixedArray(inner, size) => {
+            let items = as_array(value)?;
+            if items.len() != *size {
+                return Err(format!("Expected {} values, got {}", size, items.len()));
+            }
+            encode(&vec![(**inner).clone(); *size], items)
+        }
+        ParamType::Tuple(types) => encode(types, as_array(value)?),
+    }
+}
+
+fn decode_value(kind: &ParamType, data: &[u8]) -> Result<Value, String> {
+    match kind {
+        ParamType::Uint(_) => Ok(Value::String(
+            U256::from_be_bytes(read_word(data, 0)?).to_string(),
+        )),
+        ParamType::Int(_) => Ok(Value::String(
+            I256::from_be_bytes(read_word(data, 0)?).to_string(),
+        )),
+        ParamType::Address => Ok(hex_value(&read_word(data, 0)?[12..])),
+        ParamType::Bool => Ok(Value::Bool(read_word(data, 0)?[WORD_SIZE - 1] != 0)),
+        ParamType::FixedBytes(size) => Ok(hex_value(&read_word(data, 0)?[..*size])),
+        ParamType::Bytes => Ok(hex_value(decode_bytes(data)?)),
+        ParamType::String => {
+            let string = std::str::from_utf8(decode_bytes(data)?).map_err(|e| e.to_string())?;
+            Ok(Value::String(string.to_string()))
+        }
+        ParamType::Array(inner) => {
+            let len = read_usize(data, 0)?;
+            let items = data.get(WORD_SIZE..).ok_or("Array out of bounds")?;
+            check_array_len(inner, len, items)?;
+            Ok(Value::Array(decode(&vec![(**inner).clone(); len], items)?))
+        }
+        ParamType::FixedArray(inner, size) => {
+            check_array_len(inner, *size, data)?;
+            Ok(Value::Array(decode(&vec![(**inner).clone(); *size], data)?))
+        }
+        ParamType::Tuple(types) => Ok(Value::Array(decode(types, data)?)),
+    }
+}
+
+/// Checks that `data` is large enough for the heads of `len` items before the items are allocated, `len` comes
+/// from the untrusted return data. The items of an empty tuple have no head, so `len` is bounded by the data size
+/// too
+fn check_array_len(inner: &ParamType, len: usize, data: &[u8]) -> Result<(), String> {
+    match len.checked_mul(inner.head_size()) {
+        Some(size) if size <= data.len() && len <= data.len() => Ok(()),
+        _ => Err(format!("Array of {} items out of bounds", len)),
+    }
+}
+
+/// Parses a comma separated list of types, dropping parameter names such as `address to`
+fn parse_params(value: &str) -> Result<Vec<ParamType>, String> {
+    split_params(value)
+        .into_iter()
+        .map(|param| ParamType::parse(split_param(param).0))
+        .collect()
+}
+
+/// Splits a comma separated list of parameters, ignoring the commas inside tuples
+pub(crate) fn split_params(value: &str) -> Vec<&str> {
+    if value.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut params = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                params.push(value[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(value[start..].trim());
+
+    params
+}
+
+/// Splits a parameter such as `address indexed to` into the type and the rest
+pub(crate) fn split_param(value: &str) -> (&str, &str) {
+    let value = value.trim();
+    let end = closing_paren(value).map(|idx| idx + 1).unwrap_or(0);
+    let end = value[end..]
+        .find(char::is_whitespace)
+        .map(|idx| end + idx)
+        .unwrap_or(value.len());
+    (&value[..end], value[end..].trim())
+}
+
+/// Index of the parenthesis closing the one at the start of `value`
+pub(crate) fn closing_paren(value: &str) -> Option<usize> {
+    if !value.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (idx, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+pub(crate) fn canonical_list(types: &[ParamType]) -> String {
+    types
+        .iter()
+        .map(ParamType::canonical)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_uint(value: &Value) -> Result<U256, String> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .map(U256::from)
+            .ok_or_else(|| format!("{} is not an unsigned integer", value)),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16),
+            None => U256::from_str_radix(string, 10),
+        }
+        .map_err(|e| format!("Invalid integer {}: {}", string, e)),
+        _ => Err(format!("{} is not an integer", value)),
+    }
+}
+
+fn parse_int(value: &Value) -> Result<I256, String> {
+    match value {
+        Value::Number(number) => number
+            .as_i64()
+            .map(I256::from)
+            .ok_or_else(|| format!("{} is not an integer", value)),
+        Value::String(string) => {
+            let (sign, digits) = match string.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", string.as_str()),
+            };
+            match digits.strip_prefix("0x") {
+                Some(hex) => I256::from_str_radix(&format!("{}{}", sign, hex), 16),
+                None => I256::from_str_radix(string, 10),
+            }
+            .map_err(|e| format!("Invalid integer {}: {}", string, e))
+        }
+        _ => Err(format!("{} is not an integer", value)),
+    }
+}
+
+fn parse_hex(value: &Value) -> Result<Vec<u8>, String> {
+    let string = value
+        .as_str()
+        .ok_or_else(|| format!("{} is not a hex string", value))?;
+    hex::decode(string.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid hex value {}: {}", string, e))
+}
+
+pub(crate) fn hex_value(bytes: &[u8]) -> Value {
+    Value::String(format!("0x{}", hex::encode(bytes)))
+}
+
+fn as_array(value: &Value) -> Result<&[Value], String> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| format!("{} is not an array", value))
+}
+
+fn usize_word(value: usize) -> Word {
+    U256::from(value as u128).to_be_bytes()
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = usize_word(bytes.len()).to_vec();
+    encoded.extend_from_slice(bytes);
+    encoded.resize(WORD_SIZE + bytes.len().div_ceil(WORD_SIZE) * WORD_SIZE, 0);
+    encoded
+}
+
+fn decode_bytes(data: &[u8]) -> Result<&[u8], String> {
+    let len = read_usize(data, 0)?;
+    data.get(WORD_SIZE..WORD_SIZE + len)
+        .ok_or_else(|| "Bytes out of bounds".to_string())
+}
+
+fn read_word(data: &[u8], offset: usize) -> Result<Word, String> {
+    data.get(offset..offset + WORD_SIZE)
+        .and_then(|word| word.try_into().ok())
+        .ok_or_else(|| "Word out of bounds".to_string())
+}
+
+fn read_usize(data: &[u8], offset: usize) -> Result<usize, String> {
+    let word = read_word(data, offset)?;
+    let (high, low) = word.split_at(WORD_SIZE - 4);
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(format!("{} is too large", U256::from_be_bytes(word)));
+    }
+    Ok(u32::from_be_bytes(low.try_into().unwrap()) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const UINT256_MAX: &str =
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    const INT256_MAX: &str =
+        "57896044618658097711785492504343953926634992332820282019728792003956564819967";
+    const INT256_MIN: &str =
+        "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+
+    fn types(value: &str) -> Vec<ParamType> {
+        parse_params(value).unwrap()
+    }
+
+    /// Encodes `values`, decodes them back and compares with `expected`
+    fn round_trip(kinds: &str, values: Value, expected: Value) {
+        let kinds = types(kinds);
+        let encoded = encode(&kinds, values.as_array().unwrap()).unwrap();
+        assert_eq!(encoded.len() % WORD_SIZE, 0);
+        assert_eq!(Value::Array(decode(&kinds, &encoded).unwrap()), expected);
+    }
+
+    fn encode_err(kinds: &str, values: Value) -> String {
+        encode(&types(kinds), values.as_array().unwrap()).unwrap_err()
+    }
+
+    #[test]
+    fn parse_types() {
+        assert_eq!(
+            ParamType::parse("(uint, address[], bytes32)[2][]")
+                .unwrap()
+                .canonical(),
+            "(uint256,address[],bytes32)[2][]"
+        );
+        assert_eq!(ParamType::parse("int").unwrap(), ParamType::Int(256));
+        for invalid in [
+            "uint7", "uint264", "int0", "bytes0", "bytes33", "uint[0]", "foo", "(uint",
+        ] {
+            assert!(ParamType::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn signature() {
+        let transfer =
+            Signature::parse("function transfer(address to, uint256 amount) returns (bool)")
+                .unwrap();
+        assert_eq!(transfer.selector(), [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(transfer.outputs, vec![ParamType::Bool]);
+
+        let balance_of = Signature::parse("balanceOf(address)(uint256)").unwrap();
+        assert_eq!(balance_of.selector(), [0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(balance_of.outputs, vec![ParamType::Uint(256)]);
+    }
+
+    #[test]
+    fn encode_call_matches_solidity_docs() {
+        let signature = Signature::parse("f(uint256,uint32[],bytes10,bytes)").unwrap();
+        let data = signature
+            .encode_call(&[
+                json!("0x123"),
+                json!(["0x456", "0x789"]),
+                json!("0x31323334353637383930"),
+                json!("0x48656c6c6f2c20776f726c6421"),
+            ])
+            .unwrap();
+        let expected = concat!(
+            "8be65246",
+            "0000000000000000000000000000000000000000000000000000000000000123",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "3132333435363738393000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000456",
+            "0000000000000000000000000000000000000000000000000000000000000789",
+            "000000000000000000000000000000000000000000000000000000000000000d",
+            "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+        );
+        assert_eq!(hex::encode(data), expected);
+    }
+
+    #[test]
+    fn uint_edges() {
+        round_trip(
+            "uint256,uint256,uint256,uint8",
+            json!(["0", UINT256_MAX, "0xff", 255]),
+            json!(["0", UINT256_MAX, "255", "255"]),
+        );
+        assert!(encode_err("uint8", json!([256])).contains("doesn't fit"));
+        assert!(encode_err("uint256", json!([-1])).contains("not an unsigned integer"));
+        assert!(encode_err("uint256", json!(["1e3"])).contains("Invalid integer"));
+    }
+
+    #[test]
+    fn int_edges() {
+        round_trip(
+            "int256,int256,int256,int8,int8,int16,int16",
+            json!([INT256_MIN, INT256_MAX, "-1", -128, "127", "0x7fff", "-0x8000"]),
+            json!([INT256_MIN, INT256_MAX, "-1", "-128", "127", "32767", "-32768"]),
+        );
+        assert!(encode_err("int8", json!([128])).contains("doesn't fit"));
+        assert!(encode_err("int8", json!(["-129"])).contains("doesn't fit"));
+        assert!(encode_err("int16", json!(["0x8000"])).contains("doesn't fit"));
+    }
+
+    #[test]
+    fn static_values() {
+        round_trip(
+            "address,bool,bool,bytes4,bytes32",
+            json!([
+                "0x00000000000000000000000000000000deadbeef",
+                true,
+                false,
+                "0x01020304",
+                format!("0x{}", "ab".repeat(32)),
+            ]),
+            json!([
+                "0x00000000000000000000000000000000deadbeef",
+                true,
+                false,
+                "0x01020304",
+                format!("0x{}", "ab".repeat(32)),
+            ]),
+        );
+        assert!(encode_err("address", json!(["0x1234"])).contains("20-byte"));
+        assert!(encode_err("bytes4", json!(["0x010203"])).contains("4 bytes"));
+    }
+
+    #[test]
+    fn dynamic_values() {
+        let values = json!([
+            "",
+            "0x",
+            "0x00",
+            "x".repeat(33),
+            format!("0x{}", "01".repeat(64))
+        ]);
+        round_trip("string,bytes,bytes,string,bytes", values.clone(), values);
+    }
+
+    #[test]
+    fn nested_arrays_and_tuples() {
+        round_trip(
+            "uint256[][],string[],uint8[2][3],(uint256,string)[],(bool,(string,address[]))",
+            json!([
+                [[1, 2], [], [3]],
+                ["one", "two", "three"],
+                [[1, 2], [3, 4], [5, 6]],
+                [[1, "a"], [2, ""]],
+                [
+                    true,
+                    ["nested", ["0x0000000000000000000000000000000000000001"]]
+                ],
+            ]),
+            json!([
+                [["1", "2"], [], ["3"]],
+                ["one", "two", "three"],
+                [["1", "2"], ["3", "4"], ["5", "6"]],
+                [["1", "a"], ["2", ""]],
+                [
+                    true,
+                    ["nested", ["0x0000000000000000000000000000000000000001"]]
+                ],
+            ]),
+        );
+        round_trip("string[2]", json!([["a", "b"]]), json!([["a", "b"]]));
+        assert!(encode_err("uint8[2]", json!([[1]])).contains("Expected 2 values"));
+        assert!(encode_err("(uint8,bool)", json!([[1]])).contains("Expected 2 values"));
+    }
+
+    #[test]
+    fn decode_rejects_malformed_data() {
+        // A dynamic array claiming 2^32 - 1 items in 64 bytes
+        let mut data = usize_word(WORD_SIZE).to_vec();
+        data.extend(usize_word(u32::MAX as usize));
+        assert!(decode(&types("uint256[]"), &data)
+            .unwrap_err()
+            .contains("out of bounds"));
+
+        // The same with empty tuples, their items take no data
+        assert!(decode(&types("()[]"), &data)
+            .unwrap_err()
+            .contains("out of bounds"));
+
+        // Offset above u32
+        let mut data = vec![0xff; WORD_SIZE];
+        data.extend(usize_word(0));
+        assert!(decode(&types("bytes"), &data)
+            .unwrap_err()
+            .contains("too large"));
+
+        // Truncated data
+        assert!(decode(&types("uint256,uint256"), &usize_word(1)).is_err());
+        let encoded = encode(&types("string"), &[json!("hello")]).unwrap();
+        assert!(decode(&types("string"), &encoded[..encoded.len() - WORD_SIZE]).is_err());
+    }
+}
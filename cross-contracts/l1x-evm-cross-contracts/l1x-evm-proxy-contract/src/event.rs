@@ -0,0 +1,160 @@
+//! Decoding of EVM event logs described by an event signature.
+use serde_json::{Map, Value};
+
+use crate::abi::{self, ParamType, Word};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventParam {
+    /// The parameter name, or its position if the signature doesn't name it
+    pub name: String,
+    pub kind: ParamType,
+    pub indexed: bool,
+}
+
+/// An EVM event parsed from a signature such as `Transfer(address indexed from, address indexed to, uint256 value)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventSignature {
+    pub name: String,
+    pub params: Vec<EventParam>,
+    pub anonymous: bool,
+}
+
+impl EventSignature {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let value = value.strip_prefix("event ").unwrap_or(value);
+        let (value, anonymous) = match value.strip_suffix("anonymous") {
+            Some(value) => (value.trim_end(), true),
+            None => (value, false),
+        };
+
+        let open = value
+            .find('(')
+            .ok_or_else(|| format!("Invalid event signature {}", value))?;
+        let name = value[..open].trim();
+        let close = abi::closing_paren(&value[open..])
+            .map(|idx| open + idx)
+            .filter(|close| *close == value.len() - 1)
+            .ok_or_else(|| format!("Invalid event signature {}", value))?;
+        if name.is_empty() {
+            return Err(format!("Invalid event signature {}", value));
+        }
+
+        let params = abi::split_params(&value[open + 1..close])
+            .into_iter()
+            .enumerate()
+            .map(|(idx, param)| parse_event_param(idx, param))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            params,
+            anonymous,
+        })
+    }
+
+    /// The keccak256 hash of the canonical signature, the first topic of non-anonymous events
+    pub fn topic(&self) -> Word {
+        let kinds = self
+            .params
+            .iter()
+            .map(|param| param.kind.clone())
+            .collect::<Vec<_>>();
+        abi::keccak256(format!("{}({})", self.name, abi::canonical_list(&kinds)).as_bytes())
+    }
+
+    /// Decodes the log parameters into a JSON object keyed by the parameter names.
+    ///
+    /// Indexed `string`, `bytes`, array and tuple parameters are stored as their keccak256 hash in
+    /// the topics, they are decoded as the hex encoded hash.
+    pub fn decode_log(&self, topics: &[Word], data: &[u8]) -> Result<Map<String, Value>, String> {
+        let topics = if self.anonymous {
+            topics
+        } else {
+            let (signature_topic, topics) = topics.split_first().ok_or("The log has no topics")?;
+            if *signature_topic != self.topic() {
+                return Err(format!("The log is not a {} event", self.name));
+            }
+            topics
+        };
+
+        let indexed_count = self.params.iter().filter(|param| param.indexed).count();
+        if topics.len() != indexed_count {
+            return Err(format!(
+                "Expected {} indexed topics, got {}",
+                indexed_count,
+                topics.len()
+            ));
+        }
+
+        let data_kinds = self
+            .params
+            .iter()
+            .filter(|param| !param.indexed)
+            .map(|param| param.kind.clone())
+            .collect::<Vec<_>>();
+        let mut data_values = abi::decode(&data_kinds, data)?.into_iter();
+        let mut topics = topics.iter();
+
+        let mut values = Map::new();
+        for param in &self.params {
+            let value = if param.indexed {
+                let topic = topics.next().expect("Topics are counted above");
+                if is_hashed_in_topic(&param.kind) {
+                    abi::hex_value(topic)
+                } else {
+                    abi::decode(std::slice::from_ref(&param.kind), topic)?.remove(0)
+                }
+            } else {
+                data_values.next().expect("Data values are decoded above")
+            };
+            values.insert(param.name.clone(), value);
+        }
+
+        Ok(values)
+    }
+}
+
+/// Parses hex encoded topics
+pub fn parse_topics(topics: &[String]) -> Result<Vec<Word>, String> {
+    topics
+        .iter()
+        .map(|topic| {
+            hex::decode(topic.trim_start_matches("0x"))
+                .map_err(|e| format!("Invalid topic {}: {}", topic, e))?
+                .try_into()
+                .map_err(|_| format!("{} is not a 32-byte topic", topic))
+        })
+        .collect()
+}
+
+fn parse_event_param(idx: usize, value: &str) -> Result<EventParam, String> {
+    let (kind, rest) = abi::split_param(value);
+
+    let mut indexed = false;
+    let mut name = None;
+    for word in rest.split_whitespace() {
+        match word {
+            "indexed" if !indexed && name.is_none() => indexed = true,
+            _ if name.is_none() => name = Some(word.to_string()),
+            _ => return Err(format!("Invalid event parameter {}", value)),
+        }
+    }
+
+    Ok(EventParam {
+        name: name.unwrap_or_else(|| idx.to_string()),
+        kind: ParamType::parse(kind)?,
+        indexed,
+    })
+}
+
+fn is_hashed_in_topic(kind: &ParamType) -> bool {
+    matches!(
+        kind,
+        ParamType::Bytes
+            | ParamType::String
+            | ParamType::Array(_)
+            | ParamType::FixedArray(_, _)
+            | ParamType::Tuple(_)
+    )
+}
@@ -0,0 +1,108 @@
+mod abi;
+mod event;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_sdk::call_contract;
+use l1x_sdk::contract;
+use l1x_sdk::contract_interaction::ContractCall;
+use l1x_sdk::types::{Address, Gas, U64};
+
+/// Gas kept for the caller when a call doesn't set its gas limit
+const EVM_CALL_GAS_RESERVE: Gas = 10_000;
+
+/// Calls the EVM contract at `contract_address` with ABI encoded call data and returns the ABI encoded result
+fn execute_evm(
+    contract_address: Address,
+    args: Vec<u8>,
+    read_only: bool,
+    gas_limit: Option<Gas>,
+) -> Vec<u8> {
+    l1x_sdk::msg(&format!("L1XVM: HEX_ARG: {}", hex::encode(&args)));
+
+    let call = ContractCall {
+        contract_address,
+        method_name: "".to_string(), // method_name is not used in case of EVM call
+        args,
+        read_only,
+        gas_limit: gas_limit
+            .unwrap_or_else(|| l1x_sdk::gas_left().saturating_sub(EVM_CALL_GAS_RESERVE)),
+    };
+
+    call_contract(&call).unwrap_or_else(|e| panic!("EVM call failed: {}", e))
+}
+
+/// Holds no state and no funds, so anyone can make any call through it
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Contract {}
+
+#[contract]
+impl Contract {
+    pub fn new() {}
+
+    /// Calls any function of a deployed EVM contract.
+    ///
+    /// `signature` is `name(inputs)` optionally followed by `(outputs)`, for example `balanceOf(address)(uint256)`.
+    /// The arguments are either JSON values in `args` or ABI encoded hex in `abi_args`. Returns the decoded outputs.
+    pub fn evm_call(
+        evm_address: Address,
+        signature: String,
+        args: Option<Vec<serde_json::Value>>,
+        abi_args: Option<String>,
+        read_only: bool,
+        gas_limit: Option<U64>,
+    ) -> Vec<serde_json::Value> {
+        let signature = abi::Signature::parse(&signature).unwrap_or_else(|e| panic!("{}", e));
+
+        let call_data = match (args, abi_args) {
+            (Some(_), Some(_)) => panic!("Only one of args and abi_args can be set"),
+            (Some(args), None) => signature
+                .encode_call(&args)
+                .unwrap_or_else(|e| panic!("{}", e)),
+            (None, Some(abi_args)) => {
+                let abi_args = hex::decode(abi_args.trim_start_matches("0x"))
+                    .unwrap_or_else(|e| panic!("Invalid abi_args: {}", e));
+                signature.encode_raw_call(&abi_args)
+            }
+            (None, None) => signature
+                .encode_call(&[])
+                .unwrap_or_else(|e| panic!("{}", e)),
+        };
+
+        let ret = execute_evm(
+            evm_address,
+            call_data,
+            read_only,
+            gas_limit.map(|gas| gas.0),
+        );
+
+        let values = signature
+            .decode_returns(&ret)
+            .unwrap_or_else(|e| panic!("err: {}", e));
+        l1x_sdk::msg(&format!(
+            "L1XVM: {} returns {}",
+            signature.name,
+            serde_json::Value::from(values.clone())
+        ));
+        values
+    }
+
+    /// Decodes an EVM log into a JSON object keyed by the event parameter names.
+    ///
+    /// `event_signature` names the indexed parameters, for example
+    /// `Transfer(address indexed from, address indexed to, uint256 value)`.
+    pub fn evm_decode_log(
+        event_signature: String,
+        topics: Vec<String>,
+        data: String,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        let signature =
+            event::EventSignature::parse(&event_signature).unwrap_or_else(|e| panic!("{}", e));
+        let topics = event::parse_topics(&topics).unwrap_or_else(|e| panic!("{}", e));
+        let data = hex::decode(data.trim_start_matches("0x"))
+            .unwrap_or_else(|e| panic!("Invalid data: {}", e));
+
+        signature
+            .decode_log(&topics, &data)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
@@ -0,0 +1,239 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_sdk::{
+    block_timestamp, caller_address,
+    types::{Address, TimeStamp, U128},
+};
+use serde::Serialize;
+
+use crate::{Contract, TreasuryEvent};
+
+pub type DealId = u64;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum DealStatus {
+    /// The coins are held by the contract
+    Funded,
+    /// Waiting for the arbiter to resolve the dispute
+    Disputed,
+    /// Released to the seller by the buyer
+    Released,
+    /// Refunded to the buyer by the seller
+    Refunded,
+    /// Reclaimed by the buyer after the deadline
+    Reclaimed,
+    /// Split between the parties by the arbiter
+    Resolved,
+}
+
+/// A native coin payment from `buyer` to `seller` held by the contract until one of the parties settles it.
+///
+/// `deadline` uses the same units as `block_timestamp()`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+pub struct Deal {
+    buyer: Address,
+    seller: Address,
+    arbiter: Address,
+    amount: U128,
+    deadline: U128,
+    status: DealStatus,
+}
+
+impl Contract {
+    pub(crate) fn internal_create_deal(
+        &mut self,
+        seller: Address,
+        arbiter: Address,
+        amount: u128,
+        deadline: TimeStamp,
+    ) -> DealId {
+        let buyer = caller_address();
+        assert_ne!(amount, 0, "Amount should be greater than 0");
+        assert_ne!(buyer, seller, "The buyer can't be the seller");
+        assert!(
+            arbiter != buyer && arbiter != seller,
+            "The arbiter can't be a party of the deal"
+        );
+        assert!(
+            deadline > block_timestamp(),
+            "The deal deadline should be in the future"
+        );
+
+        // Transfer `amount` from the caller to the contract's address
+        l1x_sdk::transfer_from_caller(amount);
+        self.lock(amount);
+
+        let deal_id = self.next_deal_id;
+        self.next_deal_id = deal_id.checked_add(1).expect("Integer overflow");
+        self.deals.insert(
+            deal_id,
+            Deal {
+                buyer,
+                seller,
+                arbiter,
+                amount: amount.into(),
+                deadline: deadline.into(),
+                status: DealStatus::Funded,
+            },
+        );
+
+        TreasuryEvent::DealCreated {
+            deal_id: deal_id.into(),
+            buyer,
+            seller,
+            arbiter,
+            amount: amount.into(),
+        }
+        .emit();
+
+        deal_id
+    }
+
+    /// The buyer pays the seller
+    pub(crate) fn internal_release_deal(&mut self, deal_id: DealId) {
+        let deal = self.open_deal(deal_id);
+        assert_eq!(
+            caller_address(),
+            deal.buyer,
+            "Only the buyer can release the deal"
+        );
+
+        self.settle_deal(deal_id, DealStatus::Released, deal.amount.0, 0);
+    }
+
+    /// The seller gives the coins back to the buyer
+    pub(crate) fn internal_refund_deal(&mut self, deal_id: DealId) {
+        let deal = self.open_deal(deal_id);
+        assert_eq!(
+            caller_address(),
+            deal.seller,
+            "Only the seller can refund the deal"
+        );
+
+        self.settle_deal(deal_id, DealStatus::Refunded, 0, deal.amount.0);
+    }
+
+    pub(crate) fn internal_dispute_deal(&mut self, deal_id: DealId) {
+        let deal = self.open_deal(deal_id);
+        let caller_id = caller_address();
+        assert!(
+            caller_id == deal.buyer || caller_id == deal.seller,
+            "Only the buyer or the seller can dispute the deal"
+        );
+        assert_eq!(
+            deal.status,
+            DealStatus::Funded,
+            "The deal {} is already disputed",
+            deal_id
+        );
+
+        self.deal_mut(deal_id).status = DealStatus::Disputed;
+
+        TreasuryEvent::DealDisputed {
+            deal_id: deal_id.into(),
+            by: caller_id,
+        }
+        .emit();
+    }
+
+    /// The arbiter pays `seller_amount` to the seller and the rest to the buyer
+    pub(crate) fn internal_resolve_dispute(&mut self, deal_id: DealId, seller_amount: u128) {
+        let deal = self.open_deal(deal_id);
+        assert_eq!(
+            caller_address(),
+            deal.arbiter,
+            "Only the arbiter can resolve the dispute"
+        );
+        assert_eq!(
+            deal.status,
+            DealStatus::Disputed,
+            "The deal {} is not disputed",
+            deal_id
+        );
+        assert!(
+            seller_amount <= deal.amount.0,
+            "The seller amount exceeds the deal amount {}",
+            deal.amount.0
+        );
+
+        self.settle_deal(
+            deal_id,
+            DealStatus::Resolved,
+            seller_amount,
+            deal.amount.0 - seller_amount,
+        );
+    }
+
+    /// The buyer takes the coins back once the deadline has passed without a dispute
+    pub(crate) fn internal_reclaim_deal(&mut self, deal_id: DealId) {
+        let deal = self.open_deal(deal_id);
+        assert_eq!(
+            caller_address(),
+            deal.buyer,
+            "Only the buyer can reclaim the deal"
+        );
+        assert_eq!(
+            deal.status,
+            DealStatus::Funded,
+            "The deal {} is disputed, only the arbiter can resolve it",
+            deal_id
+        );
+        assert!(
+            block_timestamp() >= deal.deadline.0,
+            "The deal {} deadline hasn't passed yet",
+            deal_id
+        );
+
+        self.settle_deal(deal_id, DealStatus::Reclaimed, 0, deal.amount.0);
+    }
+
+    pub(crate) fn internal_deal(&self, deal_id: DealId) -> Option<Deal> {
+        self.deals.get(&deal_id).cloned()
+    }
+
+    fn settle_deal(
+        &mut self,
+        deal_id: DealId,
+        status: DealStatus,
+        seller_amount: u128,
+        buyer_amount: u128,
+    ) {
+        let deal = self.deal_mut(deal_id);
+        deal.status = status;
+        let (seller, buyer) = (deal.seller, deal.buyer);
+
+        self.unlock(seller_amount + buyer_amount);
+        // Transfer the amounts from the contract's address to the parties
+        if seller_amount != 0 {
+            l1x_sdk::transfer_to(&seller, seller_amount);
+        }
+        if buyer_amount != 0 {
+            l1x_sdk::transfer_to(&buyer, buyer_amount);
+        }
+
+        TreasuryEvent::DealSettled {
+            deal_id: deal_id.into(),
+            status,
+            seller_amount: seller_amount.into(),
+            buyer_amount: buyer_amount.into(),
+        }
+        .emit();
+    }
+
+    /// Returns the deal if it's not settled yet
+    fn open_deal(&self, deal_id: DealId) -> Deal {
+        let deal = self
+            .internal_deal(deal_id)
+            .unwrap_or_else(|| panic!("Deal {} doesn't exist", deal_id));
+        assert!(
+            deal.status == DealStatus::Funded || deal.status == DealStatus::Disputed,
+            "The deal {} is {:?}",
+            deal_id,
+            deal.status
+        );
+        deal
+    }
+
+    fn deal_mut(&mut self, deal_id: DealId) -> &mut Deal {
+        self.deals.get_mut(&deal_id).expect("Can't find the deal")
+    }
+}
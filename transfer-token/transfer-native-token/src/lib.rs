@@ -1,22 +1,524 @@
-use l1x_sdk::contract;
+mod escrow;
+mod multisig;
+mod stream;
+#[cfg(test)]
+mod test_host;
 
+use borsh::{BorshDeserialize, BorshSerialize};
+use escrow::{Deal, DealId, DealStatus};
+use l1x_sdk::contract;
+use l1x_sdk::store::LookupMap;
 use l1x_sdk::types::Address;
-use l1x_sdk::types::U128;
+use l1x_sdk::types::{TimeStamp, U128, U64};
+use l1x_sdk::{block_timestamp, caller_address, contract_owner_address, emit_event_experimental};
+use multisig::{Multisig, Proposal, ProposalStatus};
+use serde::Serialize;
+use stream::{Stream, StreamId};
+
+/// Key for the storage of the contract data.
+const STORAGE_CONTRACT_KEY: &[u8] = b"state";
+
+/// Key for the storage of the spenders.
+const STORAGE_SPENDERS_KEY: &[u8] = b"spenders";
+
+/// Key for the storage of the deposits made by `fund_contract` callers.
+const STORAGE_DEPOSITS_KEY: &[u8] = b"deposits";
+
+/// Key for the storage of the payment streams.
+const STORAGE_STREAMS_KEY: &[u8] = b"streams";
+
+/// Key for the storage of the escrow deals.
+const STORAGE_DEALS_KEY: &[u8] = b"deals";
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+pub struct Spender {
+    /// Amount that can be paid out in every spending limit period
+    daily_limit: U128,
+    /// Start of the current spending period
+    period_start: U128,
+    /// Amount paid out since `period_start`
+    spent: U128,
+}
+
+impl Spender {
+    fn new(daily_limit: u128) -> Self {
+        Self {
+            daily_limit: daily_limit.into(),
+            period_start: 0.into(),
+            spent: 0.into(),
+        }
+    }
 
-pub struct Contract {}
+    /// Starts a new period if the current one is over
+    fn refresh(&mut self, now: TimeStamp, period: TimeStamp) {
+        if now >= self.period_start.0.saturating_add(period) {
+            self.period_start = now.into();
+            self.spent = 0.into();
+        }
+    }
+
+    fn remaining(&self) -> u128 {
+        self.daily_limit.0.saturating_sub(self.spent.0)
+    }
+
+    fn spend(&mut self, amount: u128) {
+        assert!(
+            amount <= self.remaining(),
+            "The daily spending limit is exceeded, remaining: {}",
+            self.remaining()
+        );
+        self.spent = (self.spent.0 + amount).into();
+    }
+}
+
+#[derive(Debug, BorshSerialize, Serialize, Clone)]
+enum TreasuryEvent {
+    Deposit {
+        from: Address,
+        amount: U128,
+    },
+    Payout {
+        spender: Address,
+        to: Address,
+        amount: U128,
+    },
+    SpenderUpdated {
+        spender: Address,
+        daily_limit: Option<U128>,
+    },
+    OwnerChanged {
+        old_owner: Address,
+        new_owner: Address,
+    },
+    ProposalCreated {
+        proposal_id: U64,
+        proposer: Address,
+        to: Address,
+        amount: U128,
+    },
+    ProposalConfirmed {
+        proposal_id: U64,
+        signer: Address,
+    },
+    ConfirmationRevoked {
+        proposal_id: U64,
+        signer: Address,
+    },
+    ProposalExecuted {
+        proposal_id: U64,
+        to: Address,
+        amount: U128,
+    },
+    StreamCreated {
+        stream_id: U64,
+        sender: Address,
+        recipient: Address,
+        total: U128,
+    },
+    StreamWithdrawn {
+        stream_id: U64,
+        recipient: Address,
+        amount: U128,
+    },
+    StreamCancelled {
+        stream_id: U64,
+        recipient_amount: U128,
+        sender_amount: U128,
+    },
+    DealCreated {
+        deal_id: U64,
+        buyer: Address,
+        seller: Address,
+        arbiter: Address,
+        amount: U128,
+    },
+    DealDisputed {
+        deal_id: U64,
+        by: Address,
+    },
+    DealSettled {
+        deal_id: U64,
+        status: DealStatus,
+        seller_amount: U128,
+        buyer_amount: U128,
+    },
+}
+
+impl TreasuryEvent {
+    fn emit(self) {
+        l1x_sdk::msg(&serde_json::to_string(&self).unwrap_or_default());
+        emit_event_experimental(self);
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Contract {
+    owner: Address,
+    spenders: LookupMap<Address, Spender>,
+    /// `fund_contract` caller -> total amount deposited
+    deposits: LookupMap<Address, u128>,
+    total_deposits: u128,
+    /// Length of the spenders' limit period in `block_timestamp()` units
+    spending_limit_period: TimeStamp,
+    multisig: Multisig,
+    streams: LookupMap<StreamId, Stream>,
+    next_stream_id: StreamId,
+    deals: LookupMap<DealId, Deal>,
+    next_deal_id: DealId,
+    /// Coins held for streams and escrow deals, they can't be paid out by the treasury
+    locked: u128,
+}
 
 #[contract]
 impl Contract {
-    pub fn new() {}
+    pub fn new(
+        signers: Vec<Address>,
+        threshold: u32,
+        proposal_lifetime: U128,
+        spending_limit_period: U128,
+    ) {
+        assert_eq!(
+            caller_address(),
+            contract_owner_address(),
+            "Only the contract owner can call this method"
+        );
+        assert!(
+            l1x_sdk::storage_read(STORAGE_CONTRACT_KEY).is_none(),
+            "The contract is already initialized"
+        );
+        assert_ne!(
+            spending_limit_period.0, 0,
+            "The spending limit period should be greater than 0"
+        );
+
+        let mut contract = Self {
+            owner: contract_owner_address(),
+            spenders: LookupMap::new(STORAGE_SPENDERS_KEY.to_vec()),
+            deposits: LookupMap::new(STORAGE_DEPOSITS_KEY.to_vec()),
+            total_deposits: 0,
+            spending_limit_period: spending_limit_period.0,
+            multisig: Multisig::new(signers, threshold, proposal_lifetime.0),
+            streams: LookupMap::new(STORAGE_STREAMS_KEY.to_vec()),
+            next_stream_id: 0,
+            deals: LookupMap::new(STORAGE_DEALS_KEY.to_vec()),
+            next_deal_id: 0,
+            locked: 0,
+        };
+        contract.save();
+    }
 
     pub fn fund_contract(amount: U128) {
+        let mut contract = Self::load();
+
         // Transfer `amount` from the caller to the contract's address
         l1x_sdk::transfer_from_caller(amount.0);
+
+        contract.record_deposit(caller_address(), amount.0);
+
+        contract.save();
     }
 
     pub fn transfer(to: Address, amount: U128) {
+        let mut contract = Self::load();
+
+        contract.payout(to, amount.0);
+
+        contract.save();
+    }
+
+    pub fn set_spender(spender: Address, daily_limit: U128) {
+        let mut contract = Self::load();
+        contract.assert_caller_is_owner();
+        // Spenders can't pay out when every payout goes through a proposal
+        assert!(
+            !contract.multisig.is_enabled(),
+            "Spenders can't be set when the multisig is enabled"
+        );
+
+        match contract.spenders.get_mut(&spender) {
+            Some(existing) => existing.daily_limit = daily_limit,
+            None => {
+                contract
+                    .spenders
+                    .insert(spender, Spender::new(daily_limit.0));
+            }
+        }
+
+        TreasuryEvent::SpenderUpdated {
+            spender,
+            daily_limit: Some(daily_limit),
+        }
+        .emit();
+
+        contract.save();
+    }
+
+    pub fn remove_spender(spender: Address) {
+        let mut contract = Self::load();
+        contract.assert_caller_is_owner();
+
+        assert!(
+            contract.spenders.remove(spender).is_some(),
+            "{} is not a spender",
+            spender
+        );
+
+        TreasuryEvent::SpenderUpdated {
+            spender,
+            daily_limit: None,
+        }
+        .emit();
+
+        contract.save();
+    }
+
+    pub fn set_owner(new_owner: Address) {
+        let mut contract = Self::load();
+        contract.assert_caller_is_owner();
+
+        let old_owner = contract.owner;
+        contract.owner = new_owner;
+
+        TreasuryEvent::OwnerChanged {
+            old_owner,
+            new_owner,
+        }
+        .emit();
+
+        contract.save();
+    }
+
+    pub fn propose_transfer(to: Address, amount: U128) -> U64 {
+        let mut contract = Self::load();
+
+        let proposer = caller_address();
+        let proposal_id = contract
+            .multisig
+            .propose(proposer, to, amount.0, block_timestamp());
+
+        TreasuryEvent::ProposalCreated {
+            proposal_id: proposal_id.into(),
+            proposer,
+            to,
+            amount,
+        }
+        .emit();
+
+        contract.save();
+
+        proposal_id.into()
+    }
+
+    pub fn confirm(proposal_id: U64) {
+        let mut contract = Self::load();
+
+        let signer = caller_address();
+        contract
+            .multisig
+            .confirm(proposal_id.0, signer, block_timestamp());
+
+        TreasuryEvent::ProposalConfirmed {
+            proposal_id,
+            signer,
+        }
+        .emit();
+
+        contract.save();
+    }
+
+    pub fn revoke_confirmation(proposal_id: U64) {
+        let mut contract = Self::load();
+
+        let signer = caller_address();
+        contract
+            .multisig
+            .revoke_confirmation(proposal_id.0, signer, block_timestamp());
+
+        TreasuryEvent::ConfirmationRevoked {
+            proposal_id,
+            signer,
+        }
+        .emit();
+
+        contract.save();
+    }
+
+    pub fn execute(proposal_id: U64) {
+        let mut contract = Self::load();
+
+        let (to, amount) =
+            contract
+                .multisig
+                .execute(proposal_id.0, caller_address(), block_timestamp());
+
+        contract.assert_available(amount);
         // Transfer `amount` from the contract's address to `to`
-        l1x_sdk::transfer_to(&to, amount.0)
+        l1x_sdk::transfer_to(&to, amount);
+
+        TreasuryEvent::ProposalExecuted {
+            proposal_id,
+            to,
+            amount: amount.into(),
+        }
+        .emit();
+
+        contract.save();
+    }
+
+    pub fn proposal(proposal_id: U64) -> Option<Proposal> {
+        let contract = Self::load();
+        contract.multisig.proposal(proposal_id.0)
+    }
+
+    pub fn proposal_status(proposal_id: U64) -> ProposalStatus {
+        let contract = Self::load();
+        contract.multisig.status(proposal_id.0, block_timestamp())
+    }
+
+    pub fn signers() -> Vec<Address> {
+        Self::load().multisig.signers()
+    }
+
+    pub fn threshold() -> u32 {
+        Self::load().multisig.threshold()
+    }
+
+    pub fn proposal_lifetime() -> U128 {
+        Self::load().multisig.proposal_lifetime().into()
+    }
+
+    pub fn create_stream(recipient: Address, total: U128, start: U128, end: U128) -> U64 {
+        let mut contract = Self::load();
+
+        let stream_id = contract.internal_create_stream(recipient, total.0, start.0, end.0);
+
+        contract.save();
+
+        stream_id.into()
+    }
+
+    pub fn withdraw_from_stream(stream_id: U64) -> U128 {
+        let mut contract = Self::load();
+
+        let amount = contract.internal_withdraw_from_stream(stream_id.0);
+
+        contract.save();
+
+        amount.into()
+    }
+
+    pub fn cancel_stream(stream_id: U64) {
+        let mut contract = Self::load();
+
+        contract.internal_cancel_stream(stream_id.0);
+
+        contract.save();
+    }
+
+    pub fn stream(stream_id: U64) -> Option<Stream> {
+        Self::load().internal_stream(stream_id.0)
+    }
+
+    pub fn stream_withdrawable(stream_id: U64) -> U128 {
+        Self::load()
+            .internal_stream_withdrawable(stream_id.0)
+            .into()
+    }
+
+    pub fn create_deal(seller: Address, arbiter: Address, amount: U128, deadline: U128) -> U64 {
+        let mut contract = Self::load();
+
+        let deal_id = contract.internal_create_deal(seller, arbiter, amount.0, deadline.0);
+
+        contract.save();
+
+        deal_id.into()
+    }
+
+    pub fn release_deal(deal_id: U64) {
+        let mut contract = Self::load();
+
+        contract.internal_release_deal(deal_id.0);
+
+        contract.save();
+    }
+
+    pub fn refund_deal(deal_id: U64) {
+        let mut contract = Self::load();
+
+        contract.internal_refund_deal(deal_id.0);
+
+        contract.save();
+    }
+
+    pub fn dispute_deal(deal_id: U64) {
+        let mut contract = Self::load();
+
+        contract.internal_dispute_deal(deal_id.0);
+
+        contract.save();
+    }
+
+    pub fn resolve_dispute(deal_id: U64, seller_amount: U128) {
+        let mut contract = Self::load();
+
+        contract.internal_resolve_dispute(deal_id.0, seller_amount.0);
+
+        contract.save();
+    }
+
+    pub fn reclaim_deal(deal_id: U64) {
+        let mut contract = Self::load();
+
+        contract.internal_reclaim_deal(deal_id.0);
+
+        contract.save();
+    }
+
+    pub fn deal(deal_id: U64) -> Option<Deal> {
+        Self::load().internal_deal(deal_id.0)
+    }
+
+    pub fn locked_balance() -> U128 {
+        Self::load().locked.into()
+    }
+
+    pub fn owner() -> Address {
+        Self::load().owner
+    }
+
+    pub fn spender(spender: Address) -> Option<Spender> {
+        let contract = Self::load();
+        contract.spenders.get(&spender).cloned()
+    }
+
+    pub fn spending_limit_period() -> U128 {
+        Self::load().spending_limit_period.into()
+    }
+
+    pub fn remaining_daily_limit(spender: Address) -> U128 {
+        let contract = Self::load();
+        match contract.spenders.get(&spender).cloned() {
+            Some(mut spender) => {
+                spender.refresh(block_timestamp(), contract.spending_limit_period);
+                spender.remaining().into()
+            }
+            None => 0.into(),
+        }
+    }
+
+    pub fn deposits_of(address: Address) -> U128 {
+        let contract = Self::load();
+        contract
+            .deposits
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+            .into()
+    }
+
+    pub fn total_deposits() -> U128 {
+        Self::load().total_deposits.into()
     }
 
     pub fn caller_balance() -> U128 {
@@ -27,3 +529,123 @@ impl Contract {
         l1x_sdk::address_balance(&l1x_sdk::contract_instance_address()).into()
     }
 }
+
+impl Contract {
+    fn record_deposit(&mut self, from: Address, amount: u128) {
+        let deposited = self.deposits.get(&from).copied().unwrap_or_default();
+        self.deposits.insert(
+            from,
+            deposited.checked_add(amount).expect("Deposit overflowed"),
+        );
+        self.total_deposits = self
+            .total_deposits
+            .checked_add(amount)
+            .expect("Total deposits overflowed");
+
+        TreasuryEvent::Deposit {
+            from,
+            amount: amount.into(),
+        }
+        .emit();
+    }
+
+    fn payout(&mut self, to: Address, amount: u128) {
+        // Otherwise the owner could bypass the signers, spenders can't be set with the multisig
+        assert!(
+            !self.multisig.is_enabled(),
+            "Payouts need an approved multisig proposal"
+        );
+        let caller_id = caller_address();
+
+        // The owner is not limited, spenders are limited by their daily limit
+        if caller_id != self.owner {
+            let spender = self
+                .spenders
+                .get_mut(&caller_id)
+                .unwrap_or_else(|| panic!("{} is neither the owner nor a spender", caller_id));
+            spender.refresh(block_timestamp(), self.spending_limit_period);
+            spender.spend(amount);
+        }
+
+        self.assert_available(amount);
+        // Transfer `amount` from the contract's address to `to`
+        l1x_sdk::transfer_to(&to, amount);
+
+        TreasuryEvent::Payout {
+            spender: caller_id,
+            to,
+            amount: amount.into(),
+        }
+        .emit();
+    }
+
+    fn lock(&mut self, amount: u128) {
+        self.locked = self
+            .locked
+            .checked_add(amount)
+            .expect("Locked balance overflowed");
+    }
+
+    fn unlock(&mut self, amount: u128) {
+        self.locked = self
+            .locked
+            .checked_sub(amount)
+            .expect("Locked balance underflowed");
+    }
+
+    /// Checks that `amount` can be paid out without touching the locked coins
+    fn assert_available(&self, amount: u128) {
+        let available = l1x_sdk::address_balance(&l1x_sdk::contract_instance_address())
+            .saturating_sub(self.locked);
+        assert!(
+            amount <= available,
+            "Insufficient treasury balance, available: {}",
+            available
+        );
+    }
+
+    fn assert_caller_is_owner(&self) {
+        assert_eq!(
+            caller_address(),
+            self.owner,
+            "Only the treasury owner can call this method"
+        );
+    }
+
+    fn load() -> Self {
+        match l1x_sdk::storage_read(STORAGE_CONTRACT_KEY) {
+            Some(bytes) => Self::try_from_slice(&bytes).unwrap(),
+            None => panic!("The contract isn't initialized"),
+        }
+    }
+
+    fn save(&mut self) {
+        l1x_sdk::storage_write(STORAGE_CONTRACT_KEY, &self.try_to_vec().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_host;
+
+    #[test]
+    #[should_panic(expected = "Spenders can't be set when the multisig is enabled")]
+    fn set_spender_with_multisig() {
+        test_host::set_caller(test_host::owner());
+        Contract::new(vec![Address::from([1; 20])], 1, 100.into(), 100.into());
+
+        Contract::set_spender(Address::from([2; 20]), 10.into());
+    }
+
+    #[test]
+    fn set_spender_without_multisig() {
+        test_host::set_caller(test_host::owner());
+        Contract::new(vec![], 0, 0.into(), 100.into());
+
+        let spender = Address::from([2; 20]);
+        Contract::set_spender(spender, 10.into());
+
+        assert_eq!(Contract::remaining_daily_limit(spender).0, 10);
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::BTreeSet;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_sdk::store::LookupMap;
+use l1x_sdk::types::{Address, TimeStamp, U128};
+use serde::Serialize;
+
+/// Key for the storage of the multisig proposals.
+const STORAGE_PROPOSALS_KEY: &[u8] = b"proposals";
+
+pub type ProposalId = u64;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum ProposalStatus {
+    /// Waiting for confirmations
+    Pending,
+    /// Has enough confirmations to be executed
+    Approved,
+    Executed,
+    Expired,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+pub struct Proposal {
+    proposer: Address,
+    to: Address,
+    amount: U128,
+    confirmations: BTreeSet<Address>,
+    expires_at: U128,
+    executed: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Multisig {
+    signers: BTreeSet<Address>,
+    threshold: u32,
+    /// How long a proposal can be confirmed and executed, in `block_timestamp()` units
+    proposal_lifetime: TimeStamp,
+    proposals: LookupMap<ProposalId, Proposal>,
+    next_proposal_id: ProposalId,
+}
+
+impl Multisig {
+    /// No signers and the zero `threshold` disable the multisig
+    pub fn new(signers: Vec<Address>, threshold: u32, proposal_lifetime: TimeStamp) -> Self {
+        let signers_count = signers.len();
+        let signers = BTreeSet::from_iter(signers);
+        assert_eq!(signers.len(), signers_count, "Signers must be unique");
+        if signers.is_empty() {
+            assert_eq!(threshold, 0, "The threshold must be 0 without signers");
+        } else {
+            assert!(
+                threshold > 0 && threshold as usize <= signers.len(),
+                "The threshold must be between 1 and the number of signers"
+            );
+            assert_ne!(
+                proposal_lifetime, 0,
+                "The proposal lifetime should be greater than 0"
+            );
+        }
+
+        Self {
+            signers,
+            threshold,
+            proposal_lifetime,
+            proposals: LookupMap::new(STORAGE_PROPOSALS_KEY.to_vec()),
+            next_proposal_id: 0,
+        }
+    }
+
+    /// Payouts need an approved proposal when the multisig is enabled
+    pub fn is_enabled(&self) -> bool {
+        !self.signers.is_empty()
+    }
+
+    pub fn proposal_lifetime(&self) -> TimeStamp {
+        self.proposal_lifetime
+    }
+
+    pub fn signers(&self) -> Vec<Address> {
+        self.signers.iter().copied().collect()
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+        self.proposals.get(&proposal_id).cloned()
+    }
+
+    pub fn status(&self, proposal_id: ProposalId, now: TimeStamp) -> ProposalStatus {
+        let proposal = self.get(proposal_id);
+        if proposal.executed {
+            ProposalStatus::Executed
+        } else if now >= proposal.expires_at.0 {
+            ProposalStatus::Expired
+        } else if proposal.confirmations.len() >= self.threshold as usize {
+            ProposalStatus::Approved
+        } else {
+            ProposalStatus::Pending
+        }
+    }
+
+    /// Creates a proposal confirmed by the proposer
+    pub fn propose(
+        &mut self,
+        proposer: Address,
+        to: Address,
+        amount: u128,
+        now: TimeStamp,
+    ) -> ProposalId {
+        self.assert_signer(&proposer);
+        assert_ne!(amount, 0, "Amount should be greater than 0");
+
+        let proposal_id = self.next_proposal_id;
+        self.next_proposal_id = proposal_id.checked_add(1).expect("Integer overflow");
+        self.proposals.insert(
+            proposal_id,
+            Proposal {
+                proposer,
+                to,
+                amount: amount.into(),
+                confirmations: BTreeSet::from([proposer]),
+                expires_at: now.saturating_add(self.proposal_lifetime).into(),
+                executed: false,
+            },
+        );
+
+        proposal_id
+    }
+
+    pub fn confirm(&mut self, proposal_id: ProposalId, signer: Address, now: TimeStamp) {
+        self.assert_signer(&signer);
+        self.assert_not_finished(proposal_id, now);
+
+        let proposal = self.get_mut(proposal_id);
+        assert!(
+            proposal.confirmations.insert(signer),
+            "{} has already confirmed the proposal {}",
+            signer,
+            proposal_id
+        );
+    }
+
+    pub fn revoke_confirmation(
+        &mut self,
+        proposal_id: ProposalId,
+        signer: Address,
+        now: TimeStamp,
+    ) {
+        self.assert_signer(&signer);
+        self.assert_not_finished(proposal_id, now);
+
+        let proposal = self.get_mut(proposal_id);
+        assert!(
+            proposal.confirmations.remove(&signer),
+            "{} hasn't confirmed the proposal {}",
+            signer,
+            proposal_id
+        );
+    }
+
+    /// Marks the approved proposal as executed and returns the payout `(to, amount)`
+    pub fn execute(
+        &mut self,
+        proposal_id: ProposalId,
+        signer: Address,
+        now: TimeStamp,
+    ) -> (Address, u128) {
+        self.assert_signer(&signer);
+        assert_eq!(
+            self.status(proposal_id, now),
+            ProposalStatus::Approved,
+            "The proposal {} is not approved",
+            proposal_id
+        );
+
+        let proposal = self.get_mut(proposal_id);
+        proposal.executed = true;
+
+        (proposal.to, proposal.amount.0)
+    }
+
+    fn get(&self, proposal_id: ProposalId) -> &Proposal {
+        self.proposals
+            .get(&proposal_id)
+            .unwrap_or_else(|| panic!("Proposal {} doesn't exist", proposal_id))
+    }
+
+    fn get_mut(&mut self, proposal_id: ProposalId) -> &mut Proposal {
+        self.proposals
+            .get_mut(&proposal_id)
+            .unwrap_or_else(|| panic!("Proposal {} doesn't exist", proposal_id))
+    }
+
+    fn assert_not_finished(&self, proposal_id: ProposalId, now: TimeStamp) {
+        let status = self.status(proposal_id, now);
+        assert!(
+            status == ProposalStatus::Pending || status == ProposalStatus::Approved,
+            "The proposal {} is {:?}",
+            proposal_id,
+            status
+        );
+    }
+
+    fn assert_signer(&self, address: &Address) {
+        assert!(
+            self.signers.contains(address),
+            "{} is not a signer",
+            address
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIFETIME: TimeStamp = 100;
+
+    fn signer(id: u8) -> Address {
+        Address::from([id; 20])
+    }
+
+    fn recipient() -> Address {
+        Address::from([0xaa; 20])
+    }
+
+    /// 2-of-3 multisig
+    fn multisig() -> Multisig {
+        Multisig::new(vec![signer(1), signer(2), signer(3)], 2, LIFETIME)
+    }
+
+    #[test]
+    fn disabled_without_signers() {
+        assert!(!Multisig::new(vec![], 0, 0).is_enabled());
+        assert!(multisig().is_enabled());
+    }
+
+    #[test]
+    #[should_panic(expected = "The threshold must be between 1 and the number of signers")]
+    fn threshold_above_signers() {
+        Multisig::new(vec![signer(1), signer(2)], 3, LIFETIME);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signers must be unique")]
+    fn duplicate_signers() {
+        Multisig::new(vec![signer(1), signer(1)], 1, LIFETIME);
+    }
+
+    #[test]
+    fn propose_confirms_by_the_proposer() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+
+        assert_eq!(proposal_id, 0);
+        assert_eq!(multisig.propose(signer(2), recipient(), 10, 0), 1);
+        let proposal = multisig.proposal(proposal_id).unwrap();
+        assert_eq!(proposal.confirmations, BTreeSet::from([signer(1)]));
+        assert_eq!(proposal.expires_at.0, LIFETIME);
+        assert_eq!(multisig.status(proposal_id, 0), ProposalStatus::Pending);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a signer")]
+    fn propose_by_non_signer() {
+        multisig().propose(recipient(), recipient(), 10, 0);
+    }
+
+    #[test]
+    fn confirm_approves_at_threshold() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+
+        multisig.confirm(proposal_id, signer(2), 1);
+        assert_eq!(multisig.status(proposal_id, 1), ProposalStatus::Approved);
+
+        multisig.revoke_confirmation(proposal_id, signer(1), 2);
+        assert_eq!(multisig.status(proposal_id, 2), ProposalStatus::Pending);
+    }
+
+    #[test]
+    #[should_panic(expected = "has already confirmed the proposal 0")]
+    fn confirm_twice() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(1), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "hasn't confirmed the proposal 0")]
+    fn revoke_without_confirmation() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.revoke_confirmation(proposal_id, signer(2), 1);
+    }
+
+    #[test]
+    fn execute_approved() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(2), 1);
+
+        assert_eq!(
+            multisig.execute(proposal_id, signer(3), 2),
+            (recipient(), 10)
+        );
+        assert_eq!(multisig.status(proposal_id, 2), ProposalStatus::Executed);
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal 0 is not approved")]
+    fn execute_pending() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.execute(proposal_id, signer(1), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal 0 is not approved")]
+    fn execute_twice() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(2), 1);
+        multisig.execute(proposal_id, signer(1), 2);
+        multisig.execute(proposal_id, signer(1), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal 0 is Executed")]
+    fn confirm_executed() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(2), 1);
+        multisig.execute(proposal_id, signer(1), 2);
+        multisig.confirm(proposal_id, signer(3), 3);
+    }
+
+    #[test]
+    fn expires_after_lifetime() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(2), LIFETIME - 1);
+
+        assert_eq!(
+            multisig.status(proposal_id, LIFETIME - 1),
+            ProposalStatus::Approved
+        );
+        assert_eq!(
+            multisig.status(proposal_id, LIFETIME),
+            ProposalStatus::Expired
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal 0 is not approved")]
+    fn execute_expired() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(2), 1);
+        multisig.execute(proposal_id, signer(1), LIFETIME);
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal 0 is Expired")]
+    fn confirm_expired() {
+        let mut multisig = multisig();
+        let proposal_id = multisig.propose(signer(1), recipient(), 10, 0);
+        multisig.confirm(proposal_id, signer(2), LIFETIME);
+    }
+}
@@ -0,0 +1,249 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use l1x_sdk::{
+    block_timestamp, caller_address,
+    types::{Address, TimeStamp, U128},
+};
+use serde::Serialize;
+
+use crate::{Contract, TreasuryEvent};
+
+pub type StreamId = u64;
+
+/// A native coin payment from `sender` to `recipient` that vests linearly between `start` and `end`.
+///
+/// `start` and `end` use the same units as `block_timestamp()`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone)]
+pub struct Stream {
+    sender: Address,
+    recipient: Address,
+    total: U128,
+    start: U128,
+    end: U128,
+    /// Amount already paid to the recipient
+    withdrawn: U128,
+    cancelled: bool,
+}
+
+impl Stream {
+    /// Amount vested by `now`
+    fn accrued(&self, now: TimeStamp) -> u128 {
+        let (total, start, end) = (self.total.0, self.start.0, self.end.0);
+        if now <= start {
+            return 0;
+        }
+        if now >= end {
+            return total;
+        }
+
+        let duration = end - start;
+        let elapsed = now - start;
+        // Split the multiplication to avoid overflowing `total * elapsed`
+        total / duration * elapsed + total % duration * elapsed / duration
+    }
+
+    /// Amount the recipient can withdraw at `now`
+    fn withdrawable(&self, now: TimeStamp) -> u128 {
+        if self.cancelled {
+            return 0;
+        }
+        self.accrued(now) - self.withdrawn.0
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_create_stream(
+        &mut self,
+        recipient: Address,
+        total: u128,
+        start: TimeStamp,
+        end: TimeStamp,
+    ) -> StreamId {
+        let sender = caller_address();
+        assert_ne!(total, 0, "Amount should be greater than 0");
+        assert_ne!(sender, recipient, "Can't create a stream to yourself");
+        assert!(start < end, "The stream start must be before the end");
+        assert!(
+            end > block_timestamp(),
+            "The stream end should be in the future"
+        );
+
+        // Transfer `total` from the caller to the contract's address
+        l1x_sdk::transfer_from_caller(total);
+        self.lock(total);
+
+        let stream_id = self.next_stream_id;
+        self.next_stream_id = stream_id.checked_add(1).expect("Integer overflow");
+        self.streams.insert(
+            stream_id,
+            Stream {
+                sender,
+                recipient,
+                total: total.into(),
+                start: start.into(),
+                end: end.into(),
+                withdrawn: 0.into(),
+                cancelled: false,
+            },
+        );
+
+        TreasuryEvent::StreamCreated {
+            stream_id: stream_id.into(),
+            sender,
+            recipient,
+            total: total.into(),
+        }
+        .emit();
+
+        stream_id
+    }
+
+    pub(crate) fn internal_withdraw_from_stream(&mut self, stream_id: StreamId) -> u128 {
+        let now = block_timestamp();
+        let stream = self.stream_mut(stream_id);
+        assert_eq!(
+            caller_address(),
+            stream.recipient,
+            "Only the recipient can withdraw from the stream"
+        );
+
+        let amount = stream.withdrawable(now);
+        assert_ne!(
+            amount, 0,
+            "Nothing to withdraw from the stream {}",
+            stream_id
+        );
+        stream.withdrawn = (stream.withdrawn.0 + amount).into();
+        let recipient = stream.recipient;
+
+        self.unlock(amount);
+        // Transfer `amount` from the contract's address to the recipient
+        l1x_sdk::transfer_to(&recipient, amount);
+
+        TreasuryEvent::StreamWithdrawn {
+            stream_id: stream_id.into(),
+            recipient,
+            amount: amount.into(),
+        }
+        .emit();
+
+        amount
+    }
+
+    /// Pays the vested amount to the recipient and returns the rest to the sender
+    pub(crate) fn internal_cancel_stream(&mut self, stream_id: StreamId) {
+        let now = block_timestamp();
+        let stream = self.stream_mut(stream_id);
+        let caller_id = caller_address();
+        assert!(
+            caller_id == stream.sender || caller_id == stream.recipient,
+            "Only the sender or the recipient can cancel the stream"
+        );
+        assert!(
+            stream.accrued(now) < stream.total.0,
+            "The stream {} is fully vested",
+            stream_id
+        );
+
+        let recipient_amount = stream.withdrawable(now);
+        let sender_amount = stream.total.0 - stream.accrued(now);
+        stream.withdrawn = (stream.withdrawn.0 + recipient_amount).into();
+        stream.cancelled = true;
+        let (sender, recipient) = (stream.sender, stream.recipient);
+
+        self.unlock(recipient_amount + sender_amount);
+        if recipient_amount != 0 {
+            l1x_sdk::transfer_to(&recipient, recipient_amount);
+        }
+        l1x_sdk::transfer_to(&sender, sender_amount);
+
+        TreasuryEvent::StreamCancelled {
+            stream_id: stream_id.into(),
+            recipient_amount: recipient_amount.into(),
+            sender_amount: sender_amount.into(),
+        }
+        .emit();
+    }
+
+    pub(crate) fn internal_stream(&self, stream_id: StreamId) -> Option<Stream> {
+        self.streams.get(&stream_id).cloned()
+    }
+
+    pub(crate) fn internal_stream_withdrawable(&self, stream_id: StreamId) -> u128 {
+        self.streams
+            .get(&stream_id)
+            .map(|stream| stream.withdrawable(block_timestamp()))
+            .unwrap_or_default()
+    }
+
+    fn stream_mut(&mut self, stream_id: StreamId) -> &mut Stream {
+        let stream = self
+            .streams
+            .get_mut(&stream_id)
+            .unwrap_or_else(|| panic!("Stream {} doesn't exist", stream_id));
+        assert!(!stream.cancelled, "The stream {} is cancelled", stream_id);
+        stream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(total: u128, start: TimeStamp, end: TimeStamp) -> Stream {
+        Stream {
+            sender: Address::from([1; 20]),
+            recipient: Address::from([2; 20]),
+            total: total.into(),
+            start: start.into(),
+            end: end.into(),
+            withdrawn: 0.into(),
+            cancelled: false,
+        }
+    }
+
+    #[test]
+    fn accrued_before_start() {
+        let stream = stream(1000, 100, 200);
+        assert_eq!(stream.accrued(0), 0);
+        assert_eq!(stream.accrued(100), 0);
+    }
+
+    #[test]
+    fn accrued_linearly() {
+        let stream = stream(1000, 100, 200);
+        assert_eq!(stream.accrued(101), 10);
+        assert_eq!(stream.accrued(150), 500);
+        assert_eq!(stream.accrued(199), 990);
+    }
+
+    #[test]
+    fn accrued_after_end() {
+        let stream = stream(1000, 100, 200);
+        assert_eq!(stream.accrued(200), 1000);
+        assert_eq!(stream.accrued(u128::MAX), 1000);
+    }
+
+    #[test]
+    fn accrued_rounds_down() {
+        let stream = stream(10, 0, 3);
+        assert_eq!(stream.accrued(1), 3);
+        assert_eq!(stream.accrued(2), 6);
+        assert_eq!(stream.accrued(3), 10);
+    }
+
+    #[test]
+    fn accrued_does_not_overflow() {
+        let stream = stream(u128::MAX, 0, u128::MAX - 1);
+        assert_eq!(stream.accrued((u128::MAX - 1) / 2), u128::MAX / 2);
+    }
+
+    #[test]
+    fn withdrawable() {
+        let mut stream = stream(1000, 100, 200);
+        stream.withdrawn = 300.into();
+        assert_eq!(stream.withdrawable(150), 200);
+
+        stream.cancelled = true;
+        assert_eq!(stream.withdrawable(150), 0);
+    }
+}